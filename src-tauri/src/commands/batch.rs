@@ -0,0 +1,370 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+use zip::write::SimpleFileOptions;
+
+use super::files::{self, RecordingFile, SharedRecentlyDeleted, UNDO_WINDOW};
+use super::settings::{OutputSettings, SharedSettings};
+use crate::error::{AppError, AppResult};
+
+/// Outcome for one recording of a batch operation
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItemResult {
+    pub path: String,
+    /// New location of the recording, for move and export
+    pub output: Option<String>,
+    pub error: Option<AppError>,
+}
+
+/// Result of a batch operation
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    pub items: Vec<BatchItemResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Pass to `undo_delete_recording` to restore everything that was trashed
+    pub undo_id: Option<u64>,
+    /// Seconds left to undo
+    pub undo_seconds: Option<u64>,
+    /// Path of the created archive, for zip
+    pub archive: Option<String>,
+}
+
+/// Payload of the `batch-progress` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchProgress {
+    pub operation: String,
+    pub completed: usize,
+    pub total: usize,
+    pub path: String,
+}
+
+/// Archive manifest describing the zipped recordings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ZipManifest {
+    app: &'static str,
+    version: &'static str,
+    created_at: String,
+    recordings: Vec<RecordingFile>,
+}
+
+/// Run `op` for every path, collecting per-item results and emitting progress
+fn run_batch<F>(app_handle: &AppHandle, operation: &str, paths: &[String], mut op: F) -> BatchResult
+where
+    F: FnMut(&str) -> AppResult<Option<String>>,
+{
+    let mut result = BatchResult::default();
+
+    for (index, path) in paths.iter().enumerate() {
+        let outcome = op(path);
+
+        match &outcome {
+            Ok(_) => result.succeeded += 1,
+            Err(_) => result.failed += 1,
+        }
+        result.items.push(BatchItemResult {
+            path: path.clone(),
+            output: outcome.as_ref().ok().cloned().flatten(),
+            error: outcome.err(),
+        });
+
+        let _ = app_handle.emit(
+            "batch-progress",
+            BatchProgress {
+                operation: operation.to_string(),
+                completed: index + 1,
+                total: paths.len(),
+                path: path.clone(),
+            },
+        );
+    }
+
+    result
+}
+
+/// Resolve `.` and `..` in `path` without touching the file system, so it
+/// can be checked before anything is created
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Resolve `destination` as a folder, creating it if needed
+fn prepare_destination(destination: &Path) -> AppResult<PathBuf> {
+    std::fs::create_dir_all(destination)
+        .map_err(|e| AppError::io("Failed to create destination folder", destination, e))?;
+    destination
+        .canonicalize()
+        .map_err(|e| AppError::io("Destination folder is not accessible", destination, e))
+}
+
+/// Target paths for the files of a recording in `destination`, refusing to overwrite
+fn targets(parts: &[PathBuf], destination: &Path) -> AppResult<Vec<PathBuf>> {
+    let targets: Vec<PathBuf> = parts
+        .iter()
+        .map(|p| destination.join(p.file_name().unwrap_or_default()))
+        .collect();
+
+    if let Some(existing) = targets.iter().find(|t| t.exists()) {
+        return Err(AppError::AlreadyExists { path: existing.clone() });
+    }
+    Ok(targets)
+}
+
+/// Move many recordings to the trash
+#[tauri::command]
+pub async fn delete_recordings(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    settings: State<'_, SharedSettings>,
+    recently_deleted: State<'_, SharedRecentlyDeleted>,
+) -> AppResult<BatchResult> {
+    let output = settings.lock().await.output.clone();
+
+    // Trashing talks to the file system, keep it off the async runtime
+    let (mut result, trashed) = tauri::async_runtime::spawn_blocking(move || {
+        let mut trashed = Vec::new();
+        let result = run_batch(&app_handle, "delete", &paths, |path| {
+            let path = files::resolve_in_library(path, &output)?;
+            let session = files::session_files(&path);
+            files::move_to_trash(&session)?;
+            trashed.extend(session);
+            Ok(None)
+        });
+        (result, trashed)
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("Delete failed: {}", e)))?;
+
+    if !trashed.is_empty() {
+        result.undo_id = Some(recently_deleted.lock().await.push(trashed));
+        result.undo_seconds = Some(UNDO_WINDOW.as_secs());
+    }
+
+    Ok(result)
+}
+
+/// Move many recordings to a folder inside the output directory
+#[tauri::command]
+pub async fn move_recordings(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    destination: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<BatchResult> {
+    let output = settings.lock().await.output.clone();
+    let directory = output.directory_path();
+    let library = directory
+        .canonicalize()
+        .map_err(|e| AppError::io("Output directory is not accessible", &directory, e))?;
+
+    // Relative destinations are folders of the library. Checked before the
+    // folder is created, and again after symlinks are resolved.
+    let destination = normalize(&library.join(destination));
+    if !destination.starts_with(&library) {
+        return Err(AppError::OutsideLibrary { path: destination });
+    }
+    let destination = prepare_destination(&destination)?;
+    if !destination.starts_with(&library) {
+        return Err(AppError::OutsideLibrary { path: destination });
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        run_batch(&app_handle, "move", &paths, |path| {
+            let path = files::resolve_in_library(path, &output)?;
+            let session = files::session_files(&path);
+            let targets = targets(&session, &destination)?;
+
+            for (i, (from, to)) in session.iter().zip(&targets).enumerate() {
+                if let Err(e) = files::move_file(from, to) {
+                    // Put back what was moved so the session stays together
+                    for (moved, back) in targets[..i].iter().zip(&session) {
+                        if let Err(e) = files::move_file(moved, back) {
+                            tracing::warn!("Failed to move {} back: {}", moved.display(), e);
+                        }
+                    }
+                    return Err(AppError::io("Failed to move file", from, e));
+                }
+            }
+            Ok(Some(targets[0].to_string_lossy().to_string()))
+        })
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("Move failed: {}", e)))
+}
+
+/// Copy many recordings to an export folder anywhere on disk
+#[tauri::command]
+pub async fn export_recordings(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    destination: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<BatchResult> {
+    let output = settings.lock().await.output.clone();
+    let destination = prepare_destination(Path::new(&destination))?;
+
+    // Copying gigabytes of video must not block the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        run_batch(&app_handle, "export", &paths, |path| {
+            let path = files::resolve_in_library(path, &output)?;
+            let parts = files::recording_parts(&path);
+            let targets = targets(&parts, &destination)?;
+
+            for (from, to) in parts.iter().zip(&targets) {
+                std::fs::copy(from, to).map_err(|e| AppError::io("Failed to copy file", from, e))?;
+            }
+            Ok(Some(targets[0].to_string_lossy().to_string()))
+        })
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("Export failed: {}", e)))
+}
+
+/// Write the recordings at `paths` and a manifest to a new zip archive at
+/// `archive_path`.
+///
+/// A recording that can't be added is reported as failed with none of its
+/// files in the archive; failing to write the archive itself fails the
+/// whole operation.
+fn write_zip(
+    app_handle: &AppHandle,
+    paths: &[String],
+    output: &OutputSettings,
+    archive_path: &Path,
+) -> AppResult<BatchResult> {
+    let file = File::create(archive_path)
+        .map_err(|e| AppError::io("Failed to create archive", archive_path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let mut names = HashSet::new();
+    let mut recordings = Vec::new();
+    let mut write_error: Option<AppError> = None;
+
+    let mut result = run_batch(app_handle, "zip", paths, |path| {
+        if let Some(e) = &write_error {
+            return Err(e.clone());
+        }
+        let path = files::resolve_in_library(path, output)?;
+        let parts = files::recording_parts(&path);
+        let sidecars = files::session_sidecars(&parts);
+
+        // Open every file before writing any, so a recording is added whole or not at all
+        let mut sources = Vec::new();
+        let mut item_names = HashSet::new();
+        for part in parts.iter().chain(&sidecars) {
+            let name = part.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if names.contains(&name) || !item_names.insert(name.clone()) {
+                return Err(AppError::Other(format!("{} is already in the archive", name)));
+            }
+            let source = File::open(part).map_err(|e| AppError::io("Failed to open file", part, e))?;
+            let metadata = source.metadata().map_err(|e| AppError::io("Failed to read file", part, e))?;
+            sources.push((part, name, source, metadata));
+        }
+        names.extend(item_names);
+
+        let mut entry: Option<RecordingFile> = None;
+        for (part, name, mut source, metadata) in sources {
+            // Video is already compressed, storing is as small and much faster
+            let options = SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored)
+                .large_file(metadata.len() >= u32::MAX as u64);
+            let written = zip
+                .start_file(name.as_str(), options)
+                .map_err(|e| AppError::Other(format!("Failed to write archive: {}", e)))
+                .and_then(|_| {
+                    std::io::copy(&mut source, &mut zip)
+                        .map_err(|e| AppError::io("Failed to write archive", archive_path, e))
+                });
+            if let Err(e) = written {
+                write_error = Some(e.clone());
+                return Err(e);
+            }
+
+            // Sidecar files travel with the recording but aren't listed
+            if sidecars.contains(part) {
+                continue;
+            }
+
+            let file = files::recording_file(part, &metadata);
+            match entry.as_mut() {
+                Some(entry) => entry.size += file.size,
+                None => entry = Some(file),
+            }
+            if parts.len() > 1 {
+                if let Some(entry) = entry.as_mut() {
+                    entry.parts.push(name);
+                }
+            }
+        }
+
+        if let Some(mut entry) = entry {
+            // Paths in the manifest are relative to the archive
+            entry.path = parts[0].file_name().unwrap_or_default().to_string_lossy().to_string();
+            recordings.push(entry);
+        }
+        Ok(None)
+    });
+    if let Some(e) = write_error {
+        return Err(e);
+    }
+
+    let manifest = ZipManifest {
+        app: "FlashScreen",
+        version: env!("CARGO_PKG_VERSION"),
+        created_at: chrono::Local::now().to_rfc3339(),
+        recordings,
+    };
+    let manifest = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| AppError::Other(format!("Failed to serialize manifest: {}", e)))?;
+
+    zip.start_file("manifest.json", SimpleFileOptions::default())
+        .and_then(|_| {
+            std::io::Write::write_all(&mut zip, manifest.as_bytes()).map_err(Into::into)
+        })
+        .and_then(|_| zip.finish().map(|_| ()))
+        .map_err(|e| AppError::Other(format!("Failed to write archive: {}", e)))?;
+
+    result.archive = Some(archive_path.to_string_lossy().to_string());
+    Ok(result)
+}
+
+/// Create a zip archive of many recordings with a `manifest.json`
+#[tauri::command]
+pub async fn zip_recordings(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    destination: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<BatchResult> {
+    let output = settings.lock().await.output.clone();
+    let archive_path = PathBuf::from(&destination);
+    if archive_path.exists() {
+        return Err(AppError::AlreadyExists { path: archive_path });
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = write_zip(&app_handle, &paths, &output, &archive_path);
+        // Don't leave a half-written archive behind
+        if result.is_err() {
+            let _ = std::fs::remove_file(&archive_path);
+        }
+        result
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("Zip failed: {}", e)))?
+}
@@ -0,0 +1,184 @@
+use serde_json::Value;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use tauri_plugin_shell::ShellExt;
+use zip::write::SimpleFileOptions;
+
+use super::settings::SharedSettings;
+use crate::error::{AppError, AppResult};
+use crate::logging;
+
+/// `text` with the home directory replaced by `~`, so shared files don't
+/// give away the user name
+fn sanitize_text(text: &str, home: &str) -> String {
+    if home.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(home, "~")
+    }
+}
+
+/// Replace the home directory in the strings of `value` with `~`
+fn sanitize(value: &mut Value, home: &str) {
+    match value {
+        Value::String(s) => *s = sanitize_text(s, home),
+        Value::Array(items) => items.iter_mut().for_each(|v| sanitize(v, home)),
+        Value::Object(object) => object.values_mut().for_each(|v| sanitize(v, home)),
+        _ => {}
+    }
+}
+
+/// Everything FFmpeg prints for `args`, or why it couldn't run
+async fn ffmpeg_output(app_handle: &AppHandle, args: &[&str]) -> String {
+    let output = match app_handle.shell().sidecar("ffmpeg") {
+        Ok(command) => command.args(args).output().await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match output {
+        Ok(output) => format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ),
+        Err(e) => format!("Failed to run FFmpeg: {}\n", e),
+    }
+}
+
+/// Name and version of the operating system, where it's easy to tell
+fn os_release() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let release = std::fs::read_to_string("/etc/os-release").ok()?;
+        release
+            .lines()
+            .find_map(|l| l.strip_prefix("PRETTY_NAME="))
+            .map(|name| name.trim_matches('"').to_string())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("sw_vers").arg("-productVersion").output().ok()?;
+        Some(format!("macOS {}", String::from_utf8_lossy(&output.stdout).trim()))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let output = std::process::Command::new("cmd").args(["/C", "ver"]).output().ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+/// Summary of the app, the system and the displays
+fn system_info(app_handle: &AppHandle, output_dir: &Path) -> String {
+    let mut lines = vec![
+        format!("FlashScreen {}", env!("CARGO_PKG_VERSION")),
+        format!("Tauri {}", tauri::VERSION),
+        format!("Webview {}", tauri::webview_version().unwrap_or_else(|e| e.to_string())),
+        format!(
+            "OS {} ({} {})",
+            os_release().unwrap_or_else(|| String::from("unknown")),
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ),
+        format!(
+            "CPUs {}",
+            std::thread::available_parallelism().map_or(0, |n| n.get())
+        ),
+    ];
+
+    match fs2::available_space(output_dir) {
+        Ok(available) => lines.push(format!("Free space in the output directory: {} MB", available / (1024 * 1024))),
+        Err(e) => lines.push(format!("Free space in the output directory: unknown ({})", e)),
+    }
+
+    match app_handle.available_monitors() {
+        Ok(monitors) => {
+            for monitor in monitors {
+                let size = monitor.size();
+                lines.push(format!(
+                    "Display {}: {}x{} at {}x scale",
+                    monitor.name().map(String::as_str).unwrap_or("unnamed"),
+                    size.width,
+                    size.height,
+                    monitor.scale_factor()
+                ));
+            }
+        }
+        Err(e) => lines.push(format!("Displays: unknown ({})", e)),
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Write the bundle entries into a zip at `path`, with the logs made free
+/// of the home directory like the settings
+fn write_bundle(path: &Path, entries: &[(String, Vec<u8>)], logs: &[PathBuf], home: &str) -> AppResult<()> {
+    let file = File::create(path).map_err(|e| AppError::io("Failed to create archive", path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let archive_error = |e: &dyn std::fmt::Display| AppError::Other(format!("Failed to write archive: {}", e));
+
+    for (name, content) in entries {
+        zip.start_file(name.as_str(), options).map_err(|e| archive_error(&e))?;
+        zip.write_all(content).map_err(|e| archive_error(&e))?;
+    }
+
+    let log_dir = logging::log_dir();
+    for log in logs {
+        let relative = log.strip_prefix(&log_dir).unwrap_or(log);
+        let name = format!("logs/{}", relative.to_string_lossy().replace('\\', "/"));
+        // Still being written to, so read what is there now
+        let content = match std::fs::read(log) {
+            Ok(content) => String::from_utf8_lossy(&content).to_string(),
+            Err(e) => format!("Failed to read {}: {}\n", log.display(), e),
+        };
+        zip.start_file(sanitize_text(&name, home), options).map_err(|e| archive_error(&e))?;
+        zip.write_all(sanitize_text(&content, home).as_bytes()).map_err(|e| archive_error(&e))?;
+    }
+
+    zip.finish().map_err(|e| archive_error(&e))?;
+    Ok(())
+}
+
+/// Zip the logs, the settings without personal paths, FFmpeg's version and
+/// build configuration and a summary of the system into `path`, to attach
+/// to bug reports. Returns the path written.
+#[tauri::command]
+pub async fn export_diagnostics(
+    app_handle: AppHandle,
+    path: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<String> {
+    let path = PathBuf::from(path);
+    let settings = settings.lock().await.clone();
+
+    let mut settings_value = serde_json::to_value(&settings)
+        .map_err(|e| AppError::Other(format!("Failed to serialize settings: {}", e)))?;
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    sanitize(&mut settings_value, &home);
+    let settings_json = serde_json::to_vec_pretty(&settings_value)
+        .map_err(|e| AppError::Other(format!("Failed to serialize settings: {}", e)))?;
+
+    let mut ffmpeg = ffmpeg_output(&app_handle, &["-hide_banner", "-version"]).await;
+    ffmpeg.push('\n');
+    ffmpeg.push_str(&ffmpeg_output(&app_handle, &["-hide_banner", "-buildconf"]).await);
+
+    let entries = vec![
+        (String::from("system.txt"), system_info(&app_handle, &settings.output.directory_path()).into_bytes()),
+        (String::from("settings.json"), settings_json),
+        (String::from("ffmpeg.txt"), ffmpeg.into_bytes()),
+    ];
+    let logs = logging::log_files();
+
+    let target = path.clone();
+    tauri::async_runtime::spawn_blocking(move || write_bundle(&target, &entries, &logs, &home))
+        .await
+        .map_err(|e| AppError::Other(format!("Failed to write archive: {}", e)))??;
+
+    tracing::info!("Exported diagnostics to {}", path.display());
+    Ok(path.to_string_lossy().to_string())
+}
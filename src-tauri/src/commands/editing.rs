@@ -0,0 +1,167 @@
+use std::path::Path;
+use tauri::{AppHandle, Emitter, State};
+
+use super::files;
+use super::settings::SharedSettings;
+use crate::error::{AppError, AppResult};
+use crate::media::capabilities;
+use crate::media::derived_path;
+use crate::media::effects::{self, CursorEffects};
+use crate::media::profile::EncoderProfile;
+use crate::media::trim::{self, TimeRange};
+use crate::media::zoom::{self, ZoomKeyframe, ZoomOverrides};
+use crate::recording::telemetry::{self, Telemetry};
+
+/// Trim a recording to one or more ranges without re-encoding where possible.
+///
+/// Pass `start`/`end` for a single cut or `ranges` to keep several parts.
+/// With `replace` the original is swapped out atomically, otherwise a new
+/// `_trimmed` file is written next to it.
+#[tauri::command]
+pub async fn trim_recording(
+    app_handle: AppHandle,
+    path: String,
+    start: Option<f64>,
+    end: Option<f64>,
+    ranges: Option<Vec<TimeRange>>,
+    replace: bool,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<String> {
+    let output = settings.lock().await.output.clone();
+    let input = files::resolve_in_library(&path, &output)?;
+
+    let ranges = ranges.unwrap_or_else(|| {
+        vec![TimeRange {
+            start: start.unwrap_or(0.0),
+            end: end.unwrap_or(f64::MAX),
+        }]
+    });
+
+    let output = if replace {
+        input.clone()
+    } else {
+        derived_path(&input, "trimmed", "mp4", |_| false)
+    };
+
+    // Write next to the target so the final rename stays on one filesystem
+    let name = output.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = output.with_file_name(format!("{}.trim.tmp", name));
+
+    if let Err(e) = trim::trim(&app_handle, &input, &temp, ranges).await {
+        let _ = std::fs::remove_file(&temp);
+        return Err(AppError::from_anyhow("Failed to trim recording", e));
+    }
+
+    std::fs::rename(&temp, &output).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        AppError::io("Failed to save trimmed recording", &output, e)
+    })?;
+
+    let output = output.to_string_lossy().to_string();
+    let _ = app_handle.emit("recordings-changed", vec![output.clone()]);
+
+    Ok(output)
+}
+
+/// Cursor telemetry recorded with `input`
+fn load_telemetry(input: &Path) -> AppResult<Telemetry> {
+    let path = telemetry::telemetry_for(input)
+        .ok_or_else(|| AppError::Other(String::from("This recording has no cursor telemetry")))?;
+    Telemetry::load(&path).map_err(|e| AppError::from_anyhow_at("Failed to read cursor telemetry", &path, e))
+}
+
+/// Re-render the cursor effects of a recording from its telemetry.
+///
+/// Uses the effects from the recording settings unless `effects` is given.
+#[tauri::command]
+pub async fn render_cursor_effects(
+    app_handle: AppHandle,
+    path: String,
+    effects: Option<CursorEffects>,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<String> {
+    let settings = settings.lock().await.clone();
+    let input = files::resolve_in_library(&path, &settings.output)?;
+
+    let telemetry = load_telemetry(&input)?;
+
+    let effects = effects.unwrap_or_else(|| settings.recording.cursor_effects());
+    let encoder = EncoderProfile::find_or_default(&settings.output.encoder_profile);
+    let encoder = capabilities::adapt(&app_handle, encoder).await;
+    let parts = files::recording_parts(&input);
+
+    effects::render_recording(&app_handle, &parts, &telemetry, &effects, &encoder)
+        .await
+        .map_err(|e| AppError::from_anyhow("Failed to render cursor effects", e))?;
+
+    let _ = app_handle.emit("recordings-changed", vec![path.clone()]);
+    Ok(path)
+}
+
+/// Zoom timeline of a recording: keyframes generated from its clicks,
+/// merged with the manual points saved for it
+#[tauri::command]
+pub async fn get_zoom_keyframes(
+    path: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Vec<ZoomKeyframe>> {
+    let settings = settings.lock().await.clone();
+    let input = files::resolve_in_library(&path, &settings.output)?;
+
+    let telemetry = load_telemetry(&input)?;
+    let overrides = ZoomOverrides::load_for(&input)
+        .map_err(|e| AppError::from_anyhow_at("Failed to read zoom points", &input, e))?;
+
+    let auto_zoom = &settings.recording.auto_zoom;
+    Ok(zoom::timeline(zoom::generate(&telemetry, auto_zoom), &overrides.points, auto_zoom))
+}
+
+/// Save the manual zoom points of a recording; an empty list removes them
+#[tauri::command]
+pub async fn save_zoom_overrides(
+    path: String,
+    points: Vec<ZoomKeyframe>,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<()> {
+    let output = settings.lock().await.output.clone();
+    let input = files::resolve_in_library(&path, &output)?;
+
+    ZoomOverrides::new(points)
+        .save_for(&input)
+        .map_err(|e| AppError::from_anyhow_at("Failed to save zoom points", &input, e))
+}
+
+/// Render a zoomed copy of a recording, returning the written files.
+///
+/// Follows the recording's zoom timeline unless `keyframes` is given.
+#[tauri::command]
+pub async fn render_auto_zoom(
+    app_handle: AppHandle,
+    path: String,
+    keyframes: Option<Vec<ZoomKeyframe>>,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Vec<String>> {
+    let keyframes = match keyframes {
+        Some(mut keyframes) => {
+            keyframes.sort_by_key(|k| k.t);
+            keyframes
+        }
+        None => get_zoom_keyframes(path.clone(), settings.clone()).await?,
+    };
+
+    let settings = settings.lock().await.clone();
+    let input = files::resolve_in_library(&path, &settings.output)?;
+    let encoder = EncoderProfile::find_or_default(&settings.output.encoder_profile);
+    let encoder = capabilities::adapt(&app_handle, encoder).await;
+    let parts = files::recording_parts(&input);
+
+    let outputs: Vec<String> = zoom::render_recording(&app_handle, &parts, &keyframes, &encoder)
+        .await
+        .map_err(|e| AppError::from_anyhow("Failed to render zoom", e))?
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+
+    let _ = app_handle.emit("recordings-changed", outputs.clone());
+    Ok(outputs)
+}
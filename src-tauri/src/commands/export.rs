@@ -0,0 +1,196 @@
+use tauri::{AppHandle, Emitter, State};
+
+use super::files;
+use super::settings::SharedSettings;
+use crate::media::animation::{self, AnimationOptions, AnimationResult};
+use crate::error::{AppError, AppResult};
+use crate::media::audio::{self, ExtractAudioOptions};
+use crate::media::capabilities;
+use crate::media::probe::{self, AudioTrack};
+use crate::media::derived_path;
+use crate::media::keystrokes;
+use crate::media::jobs::{self, ExportJob, SharedExportQueue};
+use crate::media::profile::EncoderProfile;
+use crate::recording::telemetry::{self, Telemetry};
+
+/// Get the encoder profiles recordings can be exported to, leaving out the
+/// ones the FFmpeg sidecar can't encode
+#[tauri::command]
+pub async fn list_encoder_profiles(app_handle: AppHandle) -> AppResult<Vec<EncoderProfile>> {
+    let mut profiles = EncoderProfile::builtin();
+    if let Ok(capabilities) = capabilities::get(&app_handle, false).await {
+        profiles.retain(|p| capabilities.missing(p).is_empty());
+    }
+    Ok(profiles)
+}
+
+/// Queue an export of a recording to an encoder profile
+#[tauri::command]
+pub async fn export_recording(
+    app_handle: AppHandle,
+    path: String,
+    profile_id: String,
+    settings: State<'_, SharedSettings>,
+    queue: State<'_, SharedExportQueue>,
+) -> AppResult<ExportJob> {
+    let (output, keystrokes) = {
+        let settings = settings.lock().await;
+        (settings.output.clone(), settings.recording.keystrokes.clone())
+    };
+    let source = files::resolve_in_library(&path, &output)?;
+    let profile = EncoderProfile::find(&profile_id)
+        .ok_or_else(|| AppError::Other(format!("Unknown encoder profile: {}", profile_id)))?;
+    let profile = match capabilities::get(&app_handle, false).await {
+        Ok(capabilities) => {
+            let missing = capabilities.missing(&profile);
+            if !missing.is_empty() {
+                return Err(AppError::FfmpegUnsupported { missing });
+            }
+            capabilities::adapt(&app_handle, profile).await
+        }
+        Err(_) => profile,
+    };
+
+    // Captions of the recorded shortcuts are burned in at export
+    let telemetry = telemetry::telemetry_for(&source)
+        .filter(|_| keystrokes.enabled && profile.video_codec.is_some())
+        .and_then(|p| Telemetry::load(&p).ok());
+    let overlay = match telemetry {
+        Some(telemetry) => {
+            let parts = files::recording_parts(&source);
+            let earlier = &parts[..parts.iter().position(|p| *p == source).unwrap_or(0)];
+            keystrokes::prepare_overlay(&app_handle, &source, earlier, &telemetry, &keystrokes)
+                .await
+                .map_err(|e| AppError::from_anyhow("Failed to prepare keystroke overlay", e))?
+        }
+        None => None,
+    };
+
+    let job = {
+        let mut queue = queue.lock().await;
+        queue.set_concurrency(output.export_concurrency as usize);
+
+        // Outputs of queued jobs don't exist yet, so check them explicitly
+        let target = derived_path(&source, &profile.id, &profile.extension, |p| {
+            queue.is_busy(&p.to_string_lossy())
+        });
+        queue.enqueue(source, target, profile, overlay)
+    };
+
+    jobs::pump(&app_handle).await;
+    Ok(job)
+}
+
+/// Get all export jobs of this session
+#[tauri::command]
+pub async fn get_export_jobs(
+    queue: State<'_, SharedExportQueue>,
+) -> AppResult<Vec<ExportJob>> {
+    Ok(queue.lock().await.jobs().to_vec())
+}
+
+/// Cancel a queued or running export job
+#[tauri::command]
+pub async fn cancel_export_job(
+    id: u64,
+    queue: State<'_, SharedExportQueue>,
+) -> AppResult<ExportJob> {
+    queue.lock().await.cancel(id).map_err(|e| AppError::from_anyhow("Failed to cancel export", e))
+}
+
+/// Queue a failed or cancelled export job again
+#[tauri::command]
+pub async fn retry_export_job(
+    app_handle: AppHandle,
+    id: u64,
+    queue: State<'_, SharedExportQueue>,
+) -> AppResult<ExportJob> {
+    let job = queue.lock().await.retry(id).map_err(|e| AppError::from_anyhow("Failed to retry export", e))?;
+    jobs::pump(&app_handle).await;
+    Ok(job)
+}
+
+/// Estimate the size of a GIF or APNG export without encoding it
+#[tauri::command]
+pub async fn estimate_animation(
+    app_handle: AppHandle,
+    path: String,
+    options: AnimationOptions,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<AnimationResult> {
+    let output = settings.lock().await.output.clone();
+    let input = files::resolve_in_library(&path, &output)?;
+
+    animation::estimate(&app_handle, &input, &options)
+        .await
+        .map_err(|e| AppError::from_anyhow("Failed to estimate animation", e))
+}
+
+/// Export a range of a recording as an optimized animated GIF or APNG
+#[tauri::command]
+pub async fn export_animation(
+    app_handle: AppHandle,
+    path: String,
+    options: AnimationOptions,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<AnimationResult> {
+    let output = settings.lock().await.output.clone();
+    let input = files::resolve_in_library(&path, &output)?;
+
+    let output = derived_path(&input, "clip", options.format.extension(), |_| false);
+    let name = output.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = output.with_file_name(format!("{}.tmp", name));
+
+    let mut result = match animation::export(&app_handle, &input, &temp, &options).await {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp);
+            return Err(AppError::from_anyhow("Failed to export animation", e));
+        }
+    };
+
+    std::fs::rename(&temp, &output).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        AppError::io("Failed to save animation", &output, e)
+    })?;
+
+    let output = output.to_string_lossy().to_string();
+    result.path = Some(output.clone());
+    let _ = app_handle.emit("recordings-changed", vec![output]);
+
+    Ok(result)
+}
+
+/// Get the audio tracks of a recording, e.g. separate microphone and system audio
+#[tauri::command]
+pub async fn get_audio_tracks(
+    app_handle: AppHandle,
+    path: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Vec<AudioTrack>> {
+    let output = settings.lock().await.output.clone();
+    let input = files::resolve_in_library(&path, &output)?;
+
+    let info = probe::probe(&app_handle, &input)
+        .await
+        .map_err(|e| AppError::from_anyhow("Failed to read audio tracks", e))?;
+    Ok(info.audio_tracks)
+}
+
+/// Extract audio tracks of a recording to WAV, FLAC, MP3 or Opus files
+#[tauri::command]
+pub async fn extract_audio(
+    app_handle: AppHandle,
+    path: String,
+    options: ExtractAudioOptions,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Vec<String>> {
+    let output = settings.lock().await.output.clone();
+    let input = files::resolve_in_library(&path, &output)?;
+
+    let outputs = audio::extract(&app_handle, &input, &options)
+        .await
+        .map_err(|e| AppError::from_anyhow("Failed to extract audio", e))?;
+
+    Ok(outputs.iter().map(|p| p.to_string_lossy().to_string()).collect())
+}
@@ -1,8 +1,75 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::State;
+use tokio::sync::Mutex;
 
-use super::settings::SharedSettings;
+use super::settings::{OutputSettings, SharedSettings};
+use crate::error::{AppError, AppResult};
+use crate::media::jobs::SharedExportQueue;
+use crate::media::{effects, zoom};
+use crate::recording::filename;
+use crate::recording::telemetry;
+
+/// How long a deleted recording can be restored with `undo_delete_recording`
+pub(crate) const UNDO_WINDOW: Duration = Duration::from_secs(30);
+
+/// A recording moved to the trash that can still be restored
+#[derive(Debug, Clone)]
+pub struct TrashedRecording {
+    pub id: u64,
+    pub paths: Vec<PathBuf>,
+    pub deleted_at: Instant,
+}
+
+/// Recently trashed recordings, newest last
+#[derive(Debug, Default)]
+pub struct RecentlyDeleted {
+    next_id: u64,
+    items: Vec<TrashedRecording>,
+}
+
+impl RecentlyDeleted {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember `paths` as one undoable delete and return its id
+    pub(crate) fn push(&mut self, paths: Vec<PathBuf>) -> u64 {
+        self.prune();
+        self.next_id += 1;
+        self.items.push(TrashedRecording {
+            id: self.next_id,
+            paths,
+            deleted_at: Instant::now(),
+        });
+        self.next_id
+    }
+
+    /// Take the delete `id` if it's still inside the undo window
+    fn take(&mut self, id: u64) -> Option<TrashedRecording> {
+        self.prune();
+        let index = self.items.iter().position(|i| i.id == id)?;
+        Some(self.items.remove(index))
+    }
+
+    fn prune(&mut self) {
+        self.items.retain(|i| i.deleted_at.elapsed() < UNDO_WINDOW);
+    }
+}
+
+pub type SharedRecentlyDeleted = Arc<Mutex<RecentlyDeleted>>;
+
+/// Result of deleting a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteResult {
+    /// Pass to `undo_delete_recording` to restore the recording
+    pub undo_id: u64,
+    /// Seconds left to undo
+    pub undo_seconds: u64,
+}
 
 /// Recording file info
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,59 +83,212 @@ pub struct RecordingFile {
     pub frame_rate: u32,
     pub created_at: String,
     pub thumbnail: Option<String>,
+    /// Paths of all parts when the recording was split, in order
+    pub parts: Vec<String>,
+    pub status: FileStatus,
+}
+
+/// Whether a library entry can be used right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileStatus {
+    Ready,
+    /// An export job is reading or writing the file
+    Encoding,
+}
+
+/// Split `FlashScreen_<timestamp>_partNNN.mp4` into its stem and part number
+fn split_part_name(name: &str) -> Option<(&str, u32)> {
+    let stem = name.strip_suffix(".mp4")?;
+    let (base, part) = stem.rsplit_once("_part")?;
+    if part.len() < 3 || !part.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((base, part.parse().ok()?))
+}
+
+/// Merge the parts of split recordings into one logical recording each
+fn group_parts(files: Vec<RecordingFile>) -> Vec<RecordingFile> {
+    let mut grouped: Vec<RecordingFile> = Vec::new();
+    let mut parts: std::collections::HashMap<String, Vec<(u32, RecordingFile)>> =
+        std::collections::HashMap::new();
+
+    for file in files {
+        match split_part_name(&file.name) {
+            Some((base, part)) => {
+                let key = PathBuf::from(&file.path)
+                    .with_file_name(base)
+                    .to_string_lossy()
+                    .to_string();
+                parts.entry(key).or_default().push((part, file));
+            }
+            None => grouped.push(file),
+        }
+    }
+
+    for (key, mut group) in parts {
+        group.sort_by_key(|(part, _)| *part);
+
+        let (_, first) = &group[0];
+        let name = PathBuf::from(&key)
+            .file_name()
+            .map(|n| format!("{}.mp4", n.to_string_lossy()))
+            .unwrap_or_default();
+
+        grouped.push(RecordingFile {
+            path: first.path.clone(),
+            name,
+            size: group.iter().map(|(_, f)| f.size).sum(),
+            duration: group.iter().map(|(_, f)| f.duration).sum(),
+            resolution: first.resolution.clone(),
+            frame_rate: first.frame_rate,
+            created_at: first.created_at.clone(),
+            thumbnail: first.thumbnail.clone(),
+            parts: group.iter().map(|(_, f)| f.path.clone()).collect(),
+            status: FileStatus::Ready,
+        });
+    }
+
+    grouped
+}
+
+/// Build the library entry for a single file
+pub(crate) fn recording_file(path: &Path, metadata: &std::fs::Metadata) -> RecordingFile {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let created_at = metadata
+        .created()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| {
+            chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+
+    RecordingFile {
+        path: path.to_string_lossy().to_string(),
+        name,
+        size: metadata.len(),
+        duration: 0.0, // TODO: Get from video metadata
+        resolution: String::from("1080p"), // TODO: Get from video metadata
+        frame_rate: 60, // TODO: Get from video metadata
+        created_at,
+        thumbnail: None, // TODO: Generate thumbnail
+        parts: Vec::new(),
+        status: FileStatus::Ready,
+    }
+}
+
+/// Collect recordings in `dir` and its subfolders (created by filename templates)
+fn scan_dir(dir: &Path, files: &mut Vec<RecordingFile>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Err(e) = scan_dir(&path, files) {
+                tracing::warn!("Failed to read directory {}: {}", path.display(), e);
+            }
+            continue;
+        }
+
+        if path.extension().is_some_and(|ext| ext == "mp4") {
+            if let Ok(metadata) = entry.metadata() {
+                files.push(recording_file(&path, &metadata));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Move a file, copying it when `to` is on another volume
+pub(crate) fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if let Err(e) = std::fs::copy(from, to) {
+        let _ = std::fs::remove_file(to);
+        return Err(e);
+    }
+    std::fs::remove_file(from)
+}
+
+/// Move the recordings below `from`, with the files belonging to them such
+/// as telemetry and zoom points, to the same place below `to`.
+///
+/// Returns how many files were moved and why others weren't.
+pub(crate) fn relocate_recordings(from: &Path, to: &Path) -> (usize, Vec<String>) {
+    let mut recordings = Vec::new();
+    if let Err(e) = scan_dir(from, &mut recordings) {
+        return (0, vec![format!("Failed to read {}: {}", from.display(), e)]);
+    }
+
+    let mut files = std::collections::BTreeSet::new();
+    for recording in &recordings {
+        let path = PathBuf::from(&recording.path);
+        if !path.starts_with(to) {
+            files.extend(session_files(&path));
+        }
+    }
+
+    let mut moved = 0;
+    let mut errors = Vec::new();
+    for file in files {
+        let Ok(relative) = file.strip_prefix(from) else {
+            continue;
+        };
+        let target = to.join(relative);
+        if target.exists() {
+            errors.push(format!("A file named {} already exists", target.display()));
+            continue;
+        }
+        let result = target
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| move_file(&file, &target));
+        match result {
+            Ok(()) => moved += 1,
+            Err(e) => errors.push(format!("Failed to move {}: {}", file.display(), e)),
+        }
+    }
+
+    (moved, errors)
 }
 
 /// Get list of recordings
 #[tauri::command]
 pub async fn get_recordings(
     settings: State<'_, SharedSettings>,
-) -> Result<Vec<RecordingFile>, String> {
-    let settings = settings.lock().await;
-    let dir = PathBuf::from(&settings.output.directory);
+    queue: State<'_, SharedExportQueue>,
+) -> AppResult<Vec<RecordingFile>> {
+    let dirs = settings.lock().await.output.library_paths();
 
-    if !dir.exists() {
-        return Ok(Vec::new());
+    let mut files = Vec::new();
+    for (i, dir) in dirs.iter().enumerate() {
+        if !dir.exists() {
+            continue;
+        }
+        // Indexed folders may be gone, only the output directory has to be readable
+        match scan_dir(dir, &mut files) {
+            Err(e) if i == 0 => return Err(AppError::io("Failed to read directory", dir, e)),
+            Err(e) => tracing::warn!("Failed to read directory {}: {}", dir.display(), e),
+            Ok(()) => {}
+        }
     }
 
-    let mut files = Vec::new();
+    // Show split recordings as a single entry
+    let mut files = group_parts(files);
 
-    match std::fs::read_dir(&dir) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "mp4") {
-                    if let Ok(metadata) = entry.metadata() {
-                        let name = path
-                            .file_name()
-                            .map(|n| n.to_string_lossy().to_string())
-                            .unwrap_or_default();
-
-                        let created_at = metadata
-                            .created()
-                            .ok()
-                            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                            .map(|d| {
-                                chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)
-                                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                                    .unwrap_or_default()
-                            })
-                            .unwrap_or_default();
-
-                        files.push(RecordingFile {
-                            path: path.to_string_lossy().to_string(),
-                            name,
-                            size: metadata.len(),
-                            duration: 0.0, // TODO: Get from video metadata
-                            resolution: String::from("1080p"), // TODO: Get from video metadata
-                            frame_rate: 60, // TODO: Get from video metadata
-                            created_at,
-                            thumbnail: None, // TODO: Generate thumbnail
-                        });
-                    }
-                }
-            }
+    // Flag files an export job is working on
+    let queue = queue.lock().await;
+    for file in files.iter_mut() {
+        if queue.is_busy(&file.path) {
+            file.status = FileStatus::Encoding;
         }
-        Err(e) => return Err(format!("Failed to read directory: {}", e)),
     }
 
     // Sort by creation time (newest first)
@@ -77,37 +297,264 @@ pub async fn get_recordings(
     Ok(files)
 }
 
-/// Delete a recording
-#[tauri::command]
-pub async fn delete_recording(path: String) -> Result<(), String> {
-    let path = PathBuf::from(&path);
-    if path.exists() {
-        std::fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))?;
+/// Resolve `path` and make sure it points to a file inside the output
+/// directory or one of the indexed library directories
+pub(crate) fn resolve_in_library(path: &str, output: &OutputSettings) -> AppResult<PathBuf> {
+    let path = PathBuf::from(path)
+        .canonicalize()
+        .map_err(|_| AppError::NotFound { path: PathBuf::from(path) })?;
+
+    let in_library = output
+        .library_paths()
+        .iter()
+        .filter_map(|library| library.canonicalize().ok())
+        .any(|library| path.starts_with(library));
+    if !in_library || !path.is_file() {
+        return Err(AppError::OutsideLibrary { path });
+    }
+
+    Ok(path)
+}
+
+/// All files belonging to the recording at `path`, including sibling parts
+pub(crate) fn recording_parts(path: &Path) -> Vec<PathBuf> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let (Some((base, _)), Some(parent)) = (split_part_name(&name), path.parent()) else {
+        return vec![path.to_path_buf()];
+    };
+
+    let mut parts: Vec<(u32, PathBuf)> = std::fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    match split_part_name(&name) {
+                        Some((b, part)) if b == base => Some((part, entry.path())),
+                        _ => None,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    parts.sort_by_key(|(part, _)| *part);
+    parts.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Files kept next to the parts of a recording session: its telemetry and
+/// zoom points, and the cursor-free capture of every part
+pub(crate) fn session_sidecars(parts: &[PathBuf]) -> Vec<PathBuf> {
+    let Some(first) = parts.first() else {
+        return Vec::new();
+    };
+
+    // Session files share its stem, e.g. `<stem>.telemetry.json`
+    let session = first
+        .parent()
+        .zip(telemetry::session_stem(first))
+        .map(|(dir, stem)| telemetry::telemetry_path(dir, &stem));
+    session
+        .into_iter()
+        .chain(zoom::overrides_path(first))
+        .chain(parts.iter().map(|part| effects::clean_path(part)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// The parts of the recording at `path` followed by its sidecar files, so
+/// moving, renaming or deleting it keeps the session together
+pub(crate) fn session_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = recording_parts(path);
+    files.extend(session_sidecars(&files));
+    files
+}
+
+/// Validate a user supplied file name, without extension
+fn validate_name(name: &str) -> AppResult<()> {
+    let name = name.trim();
+    let invalid = |reason: String| {
+        Err(AppError::InvalidName {
+            name: name.to_string(),
+            reason,
+        })
+    };
+    if name.is_empty() {
+        return invalid("Name cannot be empty".to_string());
+    }
+    if name.contains(['/', '\\']) || name == "." || name == ".." {
+        return invalid("Name cannot contain path separators".to_string());
+    }
+    // Before the sanitized comparison, which would prefix reserved names
+    if filename::is_reserved_name(name) {
+        return invalid(format!("'{}' is a reserved name", name));
+    }
+    if filename::sanitize_component(name) != name {
+        return invalid("Name contains characters that are not allowed in file names".to_string());
     }
     Ok(())
 }
 
-/// Rename a recording
+/// Move files to the OS trash (the freedesktop Trash on Linux)
+pub(crate) fn move_to_trash(paths: &[PathBuf]) -> AppResult<()> {
+    trash::delete_all(paths).map_err(|e| AppError::Other(format!("Failed to move file to trash: {}", e)))
+}
+
+/// Restore files previously moved to the trash by `move_to_trash`
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+fn restore_from_trash(paths: &[PathBuf]) -> AppResult<()> {
+    let items = trash::os_limited::list()
+        .map_err(|e| AppError::Other(format!("Failed to read trash: {}", e)))?;
+
+    // The newest trash entry for each path is the one we deleted
+    let mut restore: Vec<trash::TrashItem> = Vec::new();
+    for path in paths {
+        let item = items
+            .iter()
+            .filter(|i| &i.original_path() == path)
+            .max_by_key(|i| i.time_deleted)
+            .ok_or_else(|| AppError::NotFound { path: path.clone() })?;
+        restore.push(item.clone());
+    }
+
+    trash::os_limited::restore_all(restore)
+        .map_err(|e| AppError::Other(format!("Failed to restore from trash: {}", e)))
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+fn restore_from_trash(_paths: &[PathBuf]) -> AppResult<()> {
+    Err(AppError::Other("Restoring from the trash is not supported on this platform".to_string()))
+}
+
+/// Delete a recording by moving it, with its parts and sidecar files, to the trash
+#[tauri::command]
+pub async fn delete_recording(
+    path: String,
+    settings: State<'_, SharedSettings>,
+    recently_deleted: State<'_, SharedRecentlyDeleted>,
+) -> AppResult<DeleteResult> {
+    let output = settings.lock().await.output.clone();
+    let path = resolve_in_library(&path, &output)?;
+    let paths = session_files(&path);
+
+    move_to_trash(&paths)?;
+
+    let undo_id = recently_deleted.lock().await.push(paths);
+    Ok(DeleteResult {
+        undo_id,
+        undo_seconds: UNDO_WINDOW.as_secs(),
+    })
+}
+
+/// Restore a recording deleted within the undo window
 #[tauri::command]
-pub async fn rename_recording(path: String, new_name: String) -> Result<String, String> {
-    let old_path = PathBuf::from(&path);
-    if !old_path.exists() {
-        return Err("File not found".to_string());
+pub async fn undo_delete_recording(
+    undo_id: u64,
+    recently_deleted: State<'_, SharedRecentlyDeleted>,
+) -> AppResult<String> {
+    let item = recently_deleted
+        .lock()
+        .await
+        .take(undo_id)
+        .ok_or_else(|| AppError::Other("The undo window for this recording has expired".to_string()))?;
+
+    if let Some(existing) = item.paths.iter().find(|p| p.exists()) {
+        return Err(AppError::AlreadyExists { path: existing.clone() });
     }
 
-    let new_path = old_path.parent()
-        .map(|p| p.join(&new_name))
-        .ok_or("Invalid path")?;
+    restore_from_trash(&item.paths)?;
+
+    Ok(item.paths[0].to_string_lossy().to_string())
+}
+
+/// Rename a recording, keeping its extension, numbered parts and sidecar files
+#[tauri::command]
+pub async fn rename_recording(
+    path: String,
+    new_name: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<String> {
+    let output = settings.lock().await.output.clone();
+    let old_path = resolve_in_library(&path, &output)?;
+    let parent = old_path.parent().ok_or_else(|| AppError::Other("Invalid path".to_string()))?;
+
+    // Keep the original extension, accepting a name that already repeats it
+    let extension = old_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let new_name = new_name.trim();
+    let new_stem = match new_name.rsplit_once('.') {
+        Some((stem, ext)) if !extension.is_empty() && ext.eq_ignore_ascii_case(&extension) => stem,
+        _ => new_name,
+    };
+    validate_name(new_stem)?;
+
+    let with_extension = |stem: &str| {
+        if extension.is_empty() {
+            stem.to_string()
+        } else {
+            format!("{}.{}", stem, extension)
+        }
+    };
+
+    // Split recordings are renamed part by part
+    let old_name = old_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let renames: Vec<(PathBuf, PathBuf)> = if split_part_name(&old_name).is_some() {
+        recording_parts(&old_path)
+            .into_iter()
+            .filter_map(|p| {
+                let name = p.file_name()?.to_string_lossy().to_string();
+                let (_, part) = split_part_name(&name)?;
+                let target = parent.join(with_extension(&format!("{}_part{:03}", new_stem, part)));
+                Some((p, target))
+            })
+            .collect()
+    } else {
+        vec![(old_path.clone(), parent.join(with_extension(new_stem)))]
+    };
+
+    // Sidecar files are named after a part, e.g. `<part>.mp4.nocursor`, or
+    // after the session, e.g. `<session>.telemetry.json`
+    let old_stem = telemetry::session_stem(&old_path).unwrap_or_default();
+    let parts: Vec<PathBuf> = renames.iter().map(|(from, _)| from.clone()).collect();
+    let sidecars: Vec<(PathBuf, PathBuf)> = session_sidecars(&parts)
+        .into_iter()
+        .filter_map(|sidecar| {
+            let name = sidecar.file_name()?.to_string_lossy().to_string();
+            let renamed = renames
+                .iter()
+                .find_map(|(from, to)| {
+                    let rest = name.strip_prefix(from.file_name()?.to_str()?)?;
+                    Some(format!("{}{}", to.file_name()?.to_string_lossy(), rest))
+                })
+                .or_else(|| name.strip_prefix(&old_stem).map(|rest| format!("{}{}", new_stem, rest)))?;
+            Some((sidecar.clone(), parent.join(renamed)))
+        })
+        .collect();
+    let renames: Vec<(PathBuf, PathBuf)> = renames.into_iter().chain(sidecars).collect();
 
-    std::fs::rename(&old_path, &new_path)
-        .map_err(|e| format!("Failed to rename file: {}", e))?;
+    // Refuse to overwrite anything before touching the first file
+    if let Some((_, existing)) = renames.iter().find(|(from, to)| to != from && to.exists()) {
+        return Err(AppError::AlreadyExists { path: existing.clone() });
+    }
+
+    for (from, to) in &renames {
+        std::fs::rename(from, to).map_err(|e| AppError::io("Failed to rename file", from, e))?;
+    }
 
-    Ok(new_path.to_string_lossy().to_string())
+    Ok(renames[0].1.to_string_lossy().to_string())
 }
 
 /// Show file in folder (file explorer)
 #[tauri::command]
-pub async fn show_in_folder(path: String) -> Result<(), String> {
+pub async fn show_in_folder(path: String) -> AppResult<()> {
     let path = PathBuf::from(&path);
     
     #[cfg(target_os = "windows")]
@@ -115,7 +562,7 @@ pub async fn show_in_folder(path: String) -> Result<(), String> {
         std::process::Command::new("explorer")
             .args(["/select,", &path.to_string_lossy()])
             .spawn()
-            .map_err(|e| format!("Failed to open explorer: {}", e))?;
+            .map_err(|e| AppError::Other(format!("Failed to open explorer: {}", e)))?;
     }
 
     #[cfg(target_os = "macos")]
@@ -123,7 +570,7 @@ pub async fn show_in_folder(path: String) -> Result<(), String> {
         std::process::Command::new("open")
             .args(["-R", &path.to_string_lossy()])
             .spawn()
-            .map_err(|e| format!("Failed to open Finder: {}", e))?;
+            .map_err(|e| AppError::Other(format!("Failed to open Finder: {}", e)))?;
     }
 
     #[cfg(target_os = "linux")]
@@ -132,7 +579,7 @@ pub async fn show_in_folder(path: String) -> Result<(), String> {
             std::process::Command::new("xdg-open")
                 .arg(parent)
                 .spawn()
-                .map_err(|e| format!("Failed to open file manager: {}", e))?;
+                .map_err(|e| AppError::Other(format!("Failed to open file manager: {}", e)))?;
         }
     }
 
@@ -141,7 +588,7 @@ pub async fn show_in_folder(path: String) -> Result<(), String> {
 
 /// Open file with default application
 #[tauri::command]
-pub async fn open_file(path: String) -> Result<(), String> {
+pub async fn open_file(path: String) -> AppResult<()> {
     let path = PathBuf::from(&path);
 
     #[cfg(target_os = "windows")]
@@ -149,7 +596,7 @@ pub async fn open_file(path: String) -> Result<(), String> {
         std::process::Command::new("cmd")
             .args(["/C", "start", "", &path.to_string_lossy()])
             .spawn()
-            .map_err(|e| format!("Failed to open file: {}", e))?;
+            .map_err(|e| AppError::Other(format!("Failed to open file: {}", e)))?;
     }
 
     #[cfg(target_os = "macos")]
@@ -157,7 +604,7 @@ pub async fn open_file(path: String) -> Result<(), String> {
         std::process::Command::new("open")
             .arg(&path)
             .spawn()
-            .map_err(|e| format!("Failed to open file: {}", e))?;
+            .map_err(|e| AppError::Other(format!("Failed to open file: {}", e)))?;
     }
 
     #[cfg(target_os = "linux")]
@@ -165,8 +612,96 @@ pub async fn open_file(path: String) -> Result<(), String> {
         std::process::Command::new("xdg-open")
             .arg(&path)
             .spawn()
-            .map_err(|e| format!("Failed to open file: {}", e))?;
+            .map_err(|e| AppError::Other(format!("Failed to open file: {}", e)))?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64, duration: f64) -> RecordingFile {
+        RecordingFile {
+            path: path.to_string(),
+            name: Path::new(path).file_name().unwrap().to_string_lossy().to_string(),
+            size,
+            duration,
+            resolution: String::from("1920x1080"),
+            frame_rate: 30,
+            created_at: String::new(),
+            thumbnail: None,
+            parts: Vec::new(),
+            status: FileStatus::Ready,
+        }
+    }
+
+    #[test]
+    fn splits_part_names() {
+        assert_eq!(
+            split_part_name("FlashScreen_20240309_140507_part002.mp4"),
+            Some(("FlashScreen_20240309_140507", 2))
+        );
+        assert_eq!(split_part_name("FlashScreen_20240309_140507.mp4"), None);
+        assert_eq!(split_part_name("demo_part2.mp4"), None);
+        assert_eq!(split_part_name("demo_partABC.mp4"), None);
+        assert_eq!(split_part_name("demo_part001.mkv"), None);
+    }
+
+    #[test]
+    fn groups_parts_in_order() {
+        let files = vec![
+            file("/videos/demo_part002.mp4", 20, 2.0),
+            file("/videos/other.mp4", 5, 1.0),
+            file("/videos/demo_part001.mp4", 10, 3.0),
+        ];
+        let mut grouped = group_parts(files);
+        grouped.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let [demo, other] = grouped.as_slice() else {
+            panic!("expected two recordings, got {:?}", grouped);
+        };
+        assert_eq!(demo.name, "demo.mp4");
+        assert_eq!(demo.path, "/videos/demo_part001.mp4");
+        assert_eq!(demo.parts, ["/videos/demo_part001.mp4", "/videos/demo_part002.mp4"]);
+        assert_eq!(demo.size, 30);
+        assert_eq!(demo.duration, 5.0);
+        assert_eq!(other.name, "other.mp4");
+        assert!(other.parts.is_empty());
+    }
+
+    #[test]
+    fn keeps_parts_of_different_folders_apart() {
+        let files = vec![file("/a/demo_part001.mp4", 1, 1.0), file("/b/demo_part001.mp4", 1, 1.0)];
+        assert_eq!(group_parts(files).len(), 2);
+    }
+
+    fn invalid_reason(name: &str) -> String {
+        match validate_name(name) {
+            Err(AppError::InvalidName { reason, .. }) => reason,
+            other => panic!("expected {:?} to be invalid, got {:?}", name, other),
+        }
+    }
+
+    #[test]
+    fn accepts_plain_names() {
+        assert!(validate_name("Demo take 2").is_ok());
+        assert!(validate_name("  trimmed  ").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        assert_eq!(invalid_reason("  "), "Name cannot be empty");
+        assert_eq!(invalid_reason("a/b"), "Name cannot contain path separators");
+        assert_eq!(invalid_reason(".."), "Name cannot contain path separators");
+        assert_eq!(invalid_reason("what?"), "Name contains characters that are not allowed in file names");
+        assert_eq!(invalid_reason("name."), "Name contains characters that are not allowed in file names");
+    }
+
+    #[test]
+    fn reports_reserved_names_as_reserved() {
+        assert_eq!(invalid_reason("CON"), "'CON' is a reserved name");
+        assert_eq!(invalid_reason("lpt1.backup"), "'lpt1.backup' is a reserved name");
+    }
+}
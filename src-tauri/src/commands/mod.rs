@@ -1,3 +1,8 @@
 pub mod recording;
 pub mod settings;
 pub mod files;
+pub mod batch;
+pub mod editing;
+pub mod export;
+pub mod profiles;
+pub mod diagnostics;
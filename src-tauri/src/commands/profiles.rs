@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::recording;
+use super::settings::{commit_settings, HotkeySettings, SharedSettings};
+use crate::config::validate::SettingsProblem;
+use crate::error::{AppError, AppResult};
+use crate::recording::profiles::{self, ProfileBundle, RecordingProfile};
+use crate::recording::state::Region;
+
+/// Register the hotkeys of `new` profiles, releasing the ones of `old`.
+///
+/// Pressing one emits `hotkey-start-profile` with the profile id, like the
+/// other hotkeys are handed to the frontend.
+pub fn sync_hotkeys(app_handle: &AppHandle, old: &[RecordingProfile], new: &[RecordingProfile]) {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+    let shortcuts = app_handle.global_shortcut();
+    for shortcut in old.iter().filter_map(|p| p.hotkey.parse::<Shortcut>().ok()) {
+        let _ = shortcuts.unregister(shortcut);
+    }
+
+    for profile in new.iter().filter(|p| !p.hotkey.is_empty()) {
+        let Ok(shortcut) = profile.hotkey.parse::<Shortcut>() else {
+            tracing::warn!("Invalid hotkey {} for profile {}", profile.hotkey, profile.name);
+            continue;
+        };
+        let (handle, id) = (app_handle.clone(), profile.id.clone());
+        let result = shortcuts.on_shortcut(shortcut, move |_app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                let _ = handle.emit("hotkey-start-profile", id.clone());
+            }
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to register {}: {}", profile.hotkey, e);
+        }
+    }
+}
+
+/// Key of the global hotkey or profile already using `hotkey`, leaving out
+/// the profile with id `id`
+fn hotkey_owner(
+    hotkeys: &HotkeySettings,
+    profiles: &[RecordingProfile],
+    hotkey: &str,
+    id: &str,
+) -> Option<String> {
+    use tauri_plugin_global_shortcut::Shortcut;
+
+    if hotkey.is_empty() {
+        return None;
+    }
+    let shortcut = hotkey.parse::<Shortcut>().ok();
+    let same = |other: &str| {
+        !other.is_empty()
+            && (other == hotkey || shortcut.is_some_and(|s| other.parse::<Shortcut>().is_ok_and(|o| o == s)))
+    };
+
+    let global = [
+        ("hotkeys.startStop", &hotkeys.start_stop),
+        ("hotkeys.pauseResume", &hotkeys.pause_resume),
+        ("hotkeys.cancel", &hotkeys.cancel),
+        ("hotkeys.toggleCamera", &hotkeys.toggle_camera),
+    ];
+    if let Some((key, _)) = global.iter().find(|(_, other)| same(other)) {
+        return Some(key.to_string());
+    }
+    profiles
+        .iter()
+        .enumerate()
+        .find(|(_, p)| p.id != id && same(&p.hotkey))
+        .map(|(i, _)| format!("profiles.{}.hotkey", i))
+}
+
+/// Change the stored profiles through `change` and commit the result
+async fn update_profiles<T>(
+    app_handle: &AppHandle,
+    change: impl FnOnce(&mut Vec<RecordingProfile>) -> AppResult<T>,
+) -> AppResult<T> {
+    let shared = app_handle.state::<SharedSettings>();
+    let mut settings = shared.lock().await;
+
+    let mut updated = settings.clone();
+    let result = change(&mut updated.profiles)?;
+
+    commit_settings(app_handle, &mut settings, updated, true)?;
+    Ok(result)
+}
+
+/// List the recording profiles
+#[tauri::command]
+pub async fn get_recording_profiles(
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Vec<RecordingProfile>> {
+    Ok(settings.lock().await.profiles.clone())
+}
+
+/// Create a profile, or replace the one with the same id; returns the saved
+/// profile with its id.
+///
+/// Fails with `INVALID_SETTINGS` when its hotkey is already in use.
+#[tauri::command]
+pub async fn save_recording_profile(
+    app_handle: AppHandle,
+    profile: RecordingProfile,
+) -> AppResult<RecordingProfile> {
+    let hotkeys = app_handle.state::<SharedSettings>().lock().await.hotkeys.clone();
+
+    update_profiles(&app_handle, move |profiles| {
+        if let Some(owner) = hotkey_owner(&hotkeys, profiles, &profile.hotkey, &profile.id) {
+            let index = profiles
+                .iter()
+                .position(|p| !profile.id.is_empty() && p.id == profile.id)
+                .unwrap_or(profiles.len());
+            let problem = SettingsProblem::new(
+                &format!("profiles.{}.hotkey", index),
+                format!("{} is already used by {}", profile.hotkey, owner),
+            );
+            return Err(AppError::InvalidSettings { problems: vec![problem] });
+        }
+
+        let saved = match profiles.iter_mut().find(|p| !profile.id.is_empty() && p.id == profile.id) {
+            Some(existing) => {
+                *existing = profile.clone();
+                profile
+            }
+            None => {
+                let id = profiles::unique_id(&profile.name, profiles);
+                let profile = RecordingProfile { id, ..profile };
+                profiles.push(profile.clone());
+                profile
+            }
+        };
+        Ok(saved)
+    })
+    .await
+}
+
+/// Delete a profile
+#[tauri::command]
+pub async fn delete_recording_profile(
+    app_handle: AppHandle,
+    id: String,
+) -> AppResult<()> {
+    update_profiles(&app_handle, |profiles| {
+        let count = profiles.len();
+        profiles.retain(|p| p.id != id);
+        if profiles.len() == count {
+            return Err(AppError::Other(format!("Recording profile not found: {}", id)));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Export profiles to a JSON file, all of them when `ids` is not given
+#[tauri::command]
+pub async fn export_recording_profiles(
+    path: String,
+    ids: Option<Vec<String>>,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<()> {
+    let profiles: Vec<RecordingProfile> = settings
+        .lock()
+        .await
+        .profiles
+        .iter()
+        .filter(|p| ids.as_ref().is_none_or(|ids| ids.contains(&p.id)))
+        .cloned()
+        .collect();
+    if profiles.is_empty() {
+        return Err(AppError::Other(String::from("No recording profiles to export")));
+    }
+
+    let path = PathBuf::from(path);
+    ProfileBundle::new(profiles)
+        .save(&path)
+        .map_err(|e| AppError::from_anyhow_at("Failed to export recording profiles", &path, e))
+}
+
+/// Add the profiles of an exported JSON file, returning them as stored.
+///
+/// Imported profiles get a new id when theirs is taken, and drop hotkeys
+/// that are already in use.
+#[tauri::command]
+pub async fn import_recording_profiles(
+    app_handle: AppHandle,
+    path: String,
+) -> AppResult<Vec<RecordingProfile>> {
+    let path = PathBuf::from(path);
+    let bundle = ProfileBundle::load(&path)
+        .map_err(|e| AppError::from_anyhow_at("Failed to import recording profiles", &path, e))?;
+    let hotkeys = app_handle.state::<SharedSettings>().lock().await.hotkeys.clone();
+
+    update_profiles(&app_handle, move |profiles| {
+        let mut imported = Vec::new();
+        for mut profile in bundle.profiles {
+            if profile.id.is_empty() || profiles.iter().any(|p| p.id == profile.id) {
+                profile.id = profiles::unique_id(&profile.name, profiles);
+            }
+            if hotkey_owner(&hotkeys, profiles, &profile.hotkey, &profile.id).is_some() {
+                profile.hotkey.clear();
+            }
+            profiles.push(profile.clone());
+            imported.push(profile);
+        }
+        Ok(imported)
+    })
+    .await
+}
+
+/// Start recording with a profile's mode, sources and output options.
+///
+/// `region` and `window_id` override what the profile stores, e.g. for a
+/// window picked just before.
+#[tauri::command]
+pub async fn start_recording_with_profile(
+    app_handle: AppHandle,
+    id: String,
+    region: Option<Region>,
+    window_id: Option<String>,
+) -> AppResult<String> {
+    let settings = app_handle.state::<SharedSettings>().lock().await.clone();
+    let profile = settings
+        .profiles
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| AppError::Other(format!("Recording profile not found: {}", id)))?;
+
+    let region = region.or(profile.region.clone());
+    let settings = profile.apply(&settings);
+    recording::start(app_handle, profile.mode, region, window_id, profile.sources, &settings).await
+}
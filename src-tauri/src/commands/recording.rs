@@ -1,10 +1,25 @@
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, Manager, State};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::recording::{FFmpegRecorder, RecordingState, RecordingStatus};
+use crate::desktop::notify;
+use crate::error::{AppError, AppResult};
+use crate::desktop::sound::{self, Cue};
+use crate::recording::{FFmpegRecorder, RecordingOptions, RecordingState, RecordingStatus};
+use crate::recording::disk::{self, MB};
+use crate::recording::ffmpeg::{AUDIO_DEVICE, SCREEN_DEVICE};
 use crate::recording::state::{RecordingMode, RecordingSources, Region};
+use crate::recording::telemetry::{self, Telemetry};
+use crate::media::audio::{self, ProcessingMode};
+use crate::media::capabilities::{self, FfmpegCapabilities};
+use crate::media::effects;
+use crate::media::profile::EncoderProfile;
+use crate::media::zoom;
+
+use super::files;
+use super::settings::{Settings, SharedSettings};
 
 /// Shared recorder state
 pub type SharedRecorder = Arc<Mutex<FFmpegRecorder>>;
@@ -26,7 +41,7 @@ pub struct RecordingStateResponse {
 #[tauri::command]
 pub async fn get_recording_state(
     state: State<'_, Arc<Mutex<RecordingState>>>,
-) -> Result<RecordingStateResponse, String> {
+) -> AppResult<RecordingStateResponse> {
     let state = state.lock().await;
     
     let duration = if let Some(start) = state.start_time {
@@ -54,33 +69,58 @@ pub async fn start_recording(
     region: Option<Region>,
     window_id: Option<String>,
     sources: RecordingSources,
-    recorder: State<'_, SharedRecorder>,
-    state: State<'_, Arc<Mutex<RecordingState>>>,
-) -> Result<String, String> {
+) -> AppResult<String> {
+    let settings = app_handle.state::<SharedSettings>().lock().await.clone();
+    start(app_handle, mode, region, window_id, sources, &settings).await
+}
+
+/// Start a recording with `settings`, which differ from the saved ones when
+/// a recording profile is applied
+pub(crate) async fn start(
+    app_handle: AppHandle,
+    mode: RecordingMode,
+    region: Option<Region>,
+    window_id: Option<String>,
+    sources: RecordingSources,
+    settings: &Settings,
+) -> AppResult<String> {
+    let mut options = recording_options(settings)?;
+    // Without a probe the recording is attempted as configured
+    if let Ok(capabilities) = capabilities::get(&app_handle, false).await {
+        check_capture(&capabilities, &sources, &options)?;
+        options.encoder = capabilities::adapt(&app_handle, options.encoder).await;
+    }
+
+    // Finish the cue before audio capture starts so it isn't recorded
+    if sources.microphone || sources.system_audio {
+        sound::play_cue_and_wait(&app_handle, Cue::Start).await;
+    } else {
+        sound::play_cue(&app_handle, Cue::Start).await;
+    }
+
+    let recorder = app_handle.state::<SharedRecorder>();
+    let state = app_handle.state::<Arc<Mutex<RecordingState>>>();
     let mut recorder = recorder.lock().await;
     let mut state = state.lock().await;
 
     // Set app handle for sidecar access
-    recorder.set_app_handle(Arc::new(app_handle));
-
-    // Get output directory from settings (use Videos/FlashScreen as default)
-    let output_dir = dirs::video_dir()
-        .map(|p| p.join("FlashScreen"))
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
+    recorder.set_app_handle(Arc::new(app_handle.clone()));
 
     // Start recording
     match recorder
         .start(
             mode,
             region.clone(),
+            window_id.as_deref(),
             sources.clone(),
-            &output_dir,
-            "1080p",
-            60,
+            options,
         )
         .await
     {
         Ok(output_path) => {
+            // Watch disk space and file size for the whole session
+            disk::spawn_monitor(app_handle.clone(), recorder.session_id());
+
             // Update state
             state.status = RecordingStatus::Recording;
             state.mode = mode;
@@ -92,23 +132,182 @@ pub async fn start_recording(
 
             Ok(output_path.to_string_lossy().to_string())
         }
-        Err(e) => Err(format!("Failed to start recording: {}", e)),
+        Err(e) => {
+            sound::play_cue(&app_handle, Cue::Error).await;
+            notify::error(&app_handle, "Recording failed to start", &e.to_string()).await;
+            Err(AppError::from_anyhow("Failed to start recording", e))
+        }
+    }
+}
+
+/// Fail when the sidecar lacks a device, encoder or filter the recording needs
+fn check_capture(
+    capabilities: &FfmpegCapabilities,
+    sources: &RecordingSources,
+    options: &RecordingOptions,
+) -> AppResult<()> {
+    let mut missing = Vec::new();
+    if !capabilities.has_input_device(SCREEN_DEVICE) {
+        missing.push(SCREEN_DEVICE.to_string());
+    }
+    if (sources.system_audio || sources.microphone) && !capabilities.has_input_device(AUDIO_DEVICE) {
+        missing.push(AUDIO_DEVICE.to_string());
     }
+    missing.extend(capabilities.missing(&options.encoder));
+    let filters = [
+        options.system_audio_filter.as_ref().filter(|_| sources.system_audio),
+        options.microphone_filter.as_ref().filter(|_| sources.microphone),
+    ];
+    for filter in filters.into_iter().flatten() {
+        missing.extend(capabilities.missing_filters(filter));
+    }
+
+    missing.sort();
+    missing.dedup();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::FfmpegUnsupported { missing })
+    }
+}
+
+/// Resolve recorder options from the output and audio settings
+fn recording_options(settings: &Settings) -> AppResult<RecordingOptions> {
+    let output = &settings.output;
+    let live_filter = |processing: &audio::AudioProcessing| {
+        processing
+            .filter(ProcessingMode::Live)
+            .map_err(|e| AppError::Other(format!("Invalid audio processing settings: {}", e)))
+    };
+
+    Ok(RecordingOptions {
+        output_dir: output.directory_path(),
+        resolution: output.resolution.clone(),
+        frame_rate: output.frame_rate,
+        max_file_size: (output.max_file_size_mb > 0).then(|| output.max_file_size_mb.saturating_mul(MB)),
+        split_duration: (output.split_duration_min > 0).then(|| output.split_duration_min.saturating_mul(60)),
+        filename_template: output.filename_template.clone(),
+        encoder: EncoderProfile::find_or_default(&output.encoder_profile),
+        system_audio_filter: live_filter(&settings.audio.system_audio)?,
+        microphone_filter: live_filter(&settings.audio.microphone)?,
+        cursor_telemetry: settings.recording.wants_cursor_telemetry(),
+        keystrokes: settings
+            .recording
+            .keystrokes
+            .enabled
+            .then_some(settings.recording.keystrokes.filter),
+        low_disk_warning: output.low_disk_warning_mb * MB,
+        min_free_space: output.min_free_space_mb * MB,
+    })
+}
+
+/// Payload of the `recording-processed` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingProcessed {
+    pub path: String,
+    pub error: Option<String>,
+}
+
+/// Run the post pass over a finished recording in the background: audio
+/// chains configured as a post pass, then cursor effects from the telemetry.
+///
+/// The status is `Encoding` until every part is processed, then
+/// `recording-processed` is emitted. Returns false if there is nothing to do.
+fn spawn_post_process(app_handle: AppHandle, path: PathBuf, settings: &Settings) -> bool {
+    let system_audio = settings.audio.system_audio.filter(ProcessingMode::Post);
+    let microphone = settings.audio.microphone.filter(ProcessingMode::Post);
+    // Keystroke-only telemetry is rendered at export, not here
+    let telemetry = telemetry::telemetry_for(&path)
+        .and_then(|p| Telemetry::load(&p).ok())
+        .filter(|t| !t.cursor.is_empty());
+    let effects = settings.recording.cursor_effects();
+    let auto_zoom = settings.recording.auto_zoom.clone();
+    let encoder = EncoderProfile::find_or_default(&settings.output.encoder_profile);
+
+    if matches!((&system_audio, &microphone), (Ok(None), Ok(None))) && telemetry.is_none() {
+        return false;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let encoder = capabilities::adapt(&app_handle, encoder).await;
+        let parts = files::recording_parts(&path);
+        let result = async {
+            let (system_audio, microphone) = (system_audio?, microphone?);
+            if system_audio.is_some() || microphone.is_some() {
+                for part in &parts {
+                    audio::post_process(&app_handle, part, system_audio.as_deref(), microphone.as_deref(), &encoder)
+                        .await?;
+                }
+            }
+            if let Some(telemetry) = &telemetry {
+                effects::render_recording(&app_handle, &parts, telemetry, &effects, &encoder).await?;
+
+                if auto_zoom.enabled {
+                    let keyframes = zoom::timeline(zoom::generate(telemetry, &auto_zoom), &[], &auto_zoom);
+                    if !keyframes.is_empty() {
+                        zoom::render_recording(&app_handle, &parts, &keyframes, &encoder).await?;
+                    }
+                }
+            }
+            anyhow::Ok(())
+        }
+        .await;
+
+        let state = app_handle.state::<Arc<Mutex<RecordingState>>>();
+        let mut state = state.lock().await;
+        // A new recording may have started meanwhile
+        if state.status == RecordingStatus::Encoding {
+            state.status = RecordingStatus::Idle;
+        }
+
+        let error = result.err().map(|e| format!("Failed to process recording: {}", e));
+        let _ = app_handle.emit(
+            "recording-processed",
+            RecordingProcessed {
+                path: path.to_string_lossy().to_string(),
+                error: error.clone(),
+            },
+        );
+        drop(state);
+
+        match error {
+            Some(error) => {
+                sound::play_cue(&app_handle, Cue::Error).await;
+                notify::error(&app_handle, "Recording processing failed", &error).await;
+            }
+            None => notify::recording_saved(&app_handle, &path).await,
+        }
+    });
+    true
 }
 
 /// Stop screen recording
 #[tauri::command]
 pub async fn stop_recording(
+    app_handle: AppHandle,
     recorder: State<'_, SharedRecorder>,
     state: State<'_, Arc<Mutex<RecordingState>>>,
-) -> Result<String, String> {
+) -> AppResult<String> {
+    let settings = app_handle.state::<SharedSettings>().lock().await.clone();
     let mut recorder = recorder.lock().await;
     let mut state = state.lock().await;
 
     match recorder.stop().await {
         Ok(Some(path)) => {
+            // Capture has ended, so the cue can't leak into the recording
+            sound::play_cue(&app_handle, Cue::Stop).await;
+
             let output = path.to_string_lossy().to_string();
-            state.status = RecordingStatus::Idle;
+            state.status = if spawn_post_process(app_handle.clone(), path.clone(), &settings) {
+                RecordingStatus::Encoding
+            } else {
+                // Probing the parts for the summary takes a moment
+                tauri::async_runtime::spawn(async move {
+                    notify::recording_saved(&app_handle, &path).await;
+                });
+                RecordingStatus::Idle
+            };
             state.start_time = None;
             Ok(output)
         }
@@ -117,34 +316,44 @@ pub async fn stop_recording(
             state.start_time = None;
             Ok(String::new())
         }
-        Err(e) => Err(format!("Failed to stop recording: {}", e)),
+        Err(e) => Err(AppError::from_anyhow("Failed to stop recording", e)),
     }
 }
 
 /// Pause screen recording
 #[tauri::command]
 pub async fn pause_recording(
+    app_handle: AppHandle,
     recorder: State<'_, SharedRecorder>,
     state: State<'_, Arc<Mutex<RecordingState>>>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut recorder = recorder.lock().await;
     let mut state = state.lock().await;
 
     match recorder.pause().await {
         Ok(_) => {
             state.status = RecordingStatus::Paused;
+            sound::play_cue(&app_handle, Cue::Pause).await;
             Ok(())
         }
-        Err(e) => Err(format!("Failed to pause recording: {}", e)),
+        Err(e) => Err(AppError::from_anyhow("Failed to pause recording", e)),
     }
 }
 
 /// Resume screen recording
 #[tauri::command]
 pub async fn resume_recording(
+    app_handle: AppHandle,
     recorder: State<'_, SharedRecorder>,
     state: State<'_, Arc<Mutex<RecordingState>>>,
-) -> Result<(), String> {
+) -> AppResult<()> {
+    let sources = state.lock().await.sources.clone();
+    if sources.microphone || sources.system_audio {
+        sound::play_cue_and_wait(&app_handle, Cue::Pause).await;
+    } else {
+        sound::play_cue(&app_handle, Cue::Pause).await;
+    }
+
     let mut recorder = recorder.lock().await;
     let mut state = state.lock().await;
 
@@ -153,7 +362,7 @@ pub async fn resume_recording(
             state.status = RecordingStatus::Recording;
             Ok(())
         }
-        Err(e) => Err(format!("Failed to resume recording: {}", e)),
+        Err(e) => Err(AppError::from_anyhow("Failed to resume recording", e)),
     }
 }
 
@@ -162,7 +371,7 @@ pub async fn resume_recording(
 pub async fn cancel_recording(
     recorder: State<'_, SharedRecorder>,
     state: State<'_, Arc<Mutex<RecordingState>>>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut recorder = recorder.lock().await;
     let mut state = state.lock().await;
 
@@ -171,14 +380,30 @@ pub async fn cancel_recording(
             state.reset();
             Ok(())
         }
-        Err(e) => Err(format!("Failed to cancel recording: {}", e)),
+        Err(e) => Err(AppError::from_anyhow("Failed to cancel recording", e)),
     }
 }
 
 /// Check if FFmpeg sidecar is available
 #[tauri::command]
-pub async fn check_ffmpeg_available(app_handle: AppHandle) -> Result<bool, String> {
-    crate::recording::ffmpeg::check_ffmpeg(&app_handle)
+pub async fn check_ffmpeg_available(app_handle: AppHandle) -> AppResult<bool> {
+    match capabilities::get(&app_handle, false).await {
+        Ok(_) => Ok(true),
+        Err(e) => {
+            tracing::warn!("FFmpeg is not available: {:#}", e);
+            Ok(false)
+        }
+    }
+}
+
+/// Encoders, muxers, filters and devices of the FFmpeg sidecar, probed again
+/// when `refresh` is set
+#[tauri::command]
+pub async fn get_ffmpeg_capabilities(
+    app_handle: AppHandle,
+    refresh: Option<bool>,
+) -> AppResult<FfmpegCapabilities> {
+    capabilities::get(&app_handle, refresh.unwrap_or(false))
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| AppError::from_anyhow("Failed to probe FFmpeg", e))
 }
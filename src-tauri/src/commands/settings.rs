@@ -1,32 +1,100 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
-use tauri::State;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use super::files;
+use super::profiles;
+use crate::config::file;
+use crate::config::layers::{ConfigLayers, Layer};
+use crate::config::migrate::{self, SETTINGS_VERSION};
+use crate::config::patch;
+use crate::config::validate::{self, SettingsProblem};
+use crate::desktop::autostart;
+use crate::desktop::notify::NotificationPreference;
+use crate::desktop::sound::{Cue, CueSound, SoundCues, SoundPlayer};
+use crate::error::{AppError, AppResult};
+use crate::logging::{LogLevel, Logging};
+use crate::media::capabilities::{self, SharedCapabilities};
+use crate::media::audio::AudioProcessing;
+use crate::media::effects::CursorEffects;
+use crate::media::keystrokes::KeystrokeOverlay;
+use crate::media::zoom::AutoZoom;
+use crate::recording::profiles::RecordingProfile;
+
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Settings {
+    /// Schema version, see `config::migrate`
+    pub version: u32,
     pub output: OutputSettings,
     pub hotkeys: HotkeySettings,
     pub recording: RecordingSettings,
     pub camera: CameraSettings,
     pub general: GeneralSettings,
+    pub audio: AudioSettings,
+    /// Named presets of mode, sources and output options
+    pub profiles: Vec<RecordingProfile>,
     pub is_first_launch: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct OutputSettings {
     pub directory: String,
     pub resolution: String,
     pub frame_rate: u32,
     pub format: String,
+    /// Split into numbered parts at about this size, 0 disables splitting.
+    /// Parts end up to a couple of seconds of video larger, see
+    /// `recording::disk::spawn_monitor`.
+    pub max_file_size_mb: u64,
+    /// Split into numbered parts every N minutes, 0 disables splitting
+    pub split_duration_min: u32,
+    /// Warn when free space in the output directory drops below this
+    pub low_disk_warning_mb: u64,
+    /// Don't start, and stop gracefully, below this much free space
+    pub min_free_space_mb: u64,
+    /// Output filename template, e.g. `{yyyy}/{mm}/FlashScreen_{date}_{time}`
+    pub filename_template: String,
+    /// Id of the encoder profile used for live capture
+    pub encoder_profile: String,
+    /// How many export jobs may run at the same time
+    pub export_concurrency: u32,
+    /// Earlier output directories whose recordings stay in the library
+    pub library_directories: Vec<String>,
+}
+
+impl OutputSettings {
+    /// Output directory, falling back to Videos/FlashScreen when none is configured
+    pub fn directory_path(&self) -> PathBuf {
+        if self.directory.is_empty() {
+            dirs::video_dir()
+                .map(|p| p.join("FlashScreen"))
+                .unwrap_or_else(|| PathBuf::from("."))
+        } else {
+            PathBuf::from(&self.directory)
+        }
+    }
+
+    /// Folders shown in the library: the output directory, then the indexed ones
+    pub fn library_paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![self.directory_path()];
+        for dir in self.library_directories.iter().map(PathBuf::from) {
+            if !paths.contains(&dir) {
+                paths.push(dir);
+            }
+        }
+        paths
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct HotkeySettings {
     pub start_stop: String,
     pub pause_resume: String,
@@ -35,7 +103,7 @@ pub struct HotkeySettings {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct RecordingSettings {
     pub show_countdown: bool,
     pub countdown_duration: u32,
@@ -43,10 +111,34 @@ pub struct RecordingSettings {
     pub highlight_clicks: bool,
     pub play_start_sound: bool,
     pub play_end_sound: bool,
+    /// Pause and error cues, volume and custom sound files
+    pub sounds: SoundCues,
+    /// Style of the cursor effects, plus the spotlight toggle
+    pub cursor_effects: CursorEffects,
+    /// Keystroke capture and the caption shown at export
+    pub keystrokes: KeystrokeOverlay,
+    /// Zoom-and-pan toward clicks, rendered as a copy after recording
+    pub auto_zoom: AutoZoom,
+}
+
+impl RecordingSettings {
+    /// Cursor effects to render, with `cursor_smoothing` and `highlight_clicks` applied
+    pub fn cursor_effects(&self) -> CursorEffects {
+        CursorEffects {
+            smoothing: if self.cursor_smoothing { self.cursor_effects.smoothing } else { 0.0 },
+            click_ripples: self.highlight_clicks,
+            ..self.cursor_effects.clone()
+        }
+    }
+
+    /// Whether any effect needs cursor telemetry
+    pub fn wants_cursor_telemetry(&self) -> bool {
+        self.cursor_smoothing || self.highlight_clicks || self.cursor_effects.spotlight || self.auto_zoom.enabled
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct CameraSettings {
     pub device_id: Option<String>,
     pub position: String,
@@ -54,55 +146,123 @@ pub struct CameraSettings {
     pub shape: String,
 }
 
+/// Processing chains, configured separately per audio source
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
+pub struct AudioSettings {
+    pub microphone: AudioProcessing,
+    pub system_audio: AudioProcessing,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            microphone: AudioProcessing::microphone(),
+            system_audio: AudioProcessing::system_audio(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct GeneralSettings {
     pub language: String,
     pub launch_at_startup: bool,
     pub minimize_to_tray: bool,
     pub show_preview_after_recording: bool,
+    /// Desktop notifications for finished recordings and failures
+    pub notifications: NotificationPreference,
+    /// Detail written to the log files
+    pub log_level: LogLevel,
 }
 
 impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            output: OutputSettings::default(),
+            hotkeys: HotkeySettings::default(),
+            recording: RecordingSettings::default(),
+            camera: CameraSettings::default(),
+            general: GeneralSettings::default(),
+            audio: AudioSettings::default(),
+            profiles: Vec::new(),
+            is_first_launch: true,
+        }
+    }
+}
+
+impl Default for OutputSettings {
     fn default() -> Self {
         let output_dir = dirs::video_dir()
             .map(|p| p.join("FlashScreen").to_string_lossy().to_string())
             .unwrap_or_default();
 
         Self {
-            output: OutputSettings {
-                directory: output_dir,
-                resolution: String::from("1080p"),
-                frame_rate: 60,
-                format: String::from("mp4"),
-            },
-            hotkeys: HotkeySettings {
-                start_stop: String::from("F1"),
-                pause_resume: String::from("F2"),
-                cancel: String::from("F3"),
-                toggle_camera: String::from("F4"),
-            },
-            recording: RecordingSettings {
-                show_countdown: false,
-                countdown_duration: 3,
-                cursor_smoothing: true,
-                highlight_clicks: false,
-                play_start_sound: true,
-                play_end_sound: true,
-            },
-            camera: CameraSettings {
-                device_id: None,
-                position: String::from("bottom-right"),
-                size: 150,
-                shape: String::from("circle"),
-            },
-            general: GeneralSettings {
-                language: String::from("zh-CN"),
-                launch_at_startup: false,
-                minimize_to_tray: true,
-                show_preview_after_recording: true,
-            },
-            is_first_launch: true,
+            directory: output_dir,
+            resolution: String::from("1080p"),
+            frame_rate: 60,
+            format: String::from("mp4"),
+            max_file_size_mb: 0,
+            split_duration_min: 0,
+            low_disk_warning_mb: 2048,
+            min_free_space_mb: 500,
+            filename_template: String::from(crate::recording::filename::DEFAULT_TEMPLATE),
+            encoder_profile: String::from(crate::media::profile::DEFAULT_PROFILE),
+            export_concurrency: 2,
+            library_directories: Vec::new(),
+        }
+    }
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            start_stop: String::from("F1"),
+            pause_resume: String::from("F2"),
+            cancel: String::from("F3"),
+            toggle_camera: String::from("F4"),
+        }
+    }
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            show_countdown: false,
+            countdown_duration: 3,
+            cursor_smoothing: true,
+            highlight_clicks: false,
+            play_start_sound: true,
+            play_end_sound: true,
+            sounds: SoundCues::default(),
+            cursor_effects: CursorEffects::default(),
+            keystrokes: KeystrokeOverlay::default(),
+            auto_zoom: AutoZoom::default(),
+        }
+    }
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            device_id: None,
+            position: String::from("bottom-right"),
+            size: 150,
+            shape: String::from("circle"),
+        }
+    }
+}
+
+impl Default for GeneralSettings {
+    fn default() -> Self {
+        Self {
+            language: String::from("zh-CN"),
+            launch_at_startup: false,
+            minimize_to_tray: true,
+            show_preview_after_recording: true,
+            notifications: NotificationPreference::default(),
+            log_level: LogLevel::default(),
         }
     }
 }
@@ -110,66 +270,388 @@ impl Default for Settings {
 pub type SharedSettings = Arc<Mutex<Settings>>;
 
 /// Get settings file path
-fn get_settings_path() -> PathBuf {
+pub fn get_settings_path() -> PathBuf {
     dirs::config_dir()
         .map(|p| p.join("FlashScreen").join("settings.json"))
         .unwrap_or_else(|| PathBuf::from("settings.json"))
 }
 
-/// Load settings from file
-pub fn load_settings() -> Settings {
+/// What went wrong loading the settings file at startup
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsReport {
+    pub problems: Vec<SettingsProblem>,
+    /// Copy of the file as it was before it got repaired
+    pub backup: Option<String>,
+}
+
+/// Copy an unusable settings file next to the original, so nothing the user
+/// configured is lost when it gets overwritten
+fn backup_settings(path: &std::path::Path) -> Option<String> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let backup = path.with_file_name(format!("settings.json.invalid-{}", stamp));
+    match std::fs::copy(path, &backup) {
+        Ok(_) => Some(backup.to_string_lossy().to_string()),
+        Err(e) => {
+            tracing::warn!("Failed to back up settings file: {}", e);
+            None
+        }
+    }
+}
+
+/// Parse, migrate and repair a user settings document, and combine it with
+/// the other configuration layers.
+///
+/// Missing fields take their defaults and unusable values are reset, so one
+/// bad value doesn't cost the rest of the configuration.
+pub fn parse_settings(content: &str, layers: &ConfigLayers) -> (Settings, Vec<SettingsProblem>) {
+    let mut value: Value = match serde_json::from_str(content) {
+        Ok(value) => value,
+        Err(e) => {
+            let problem = SettingsProblem::new("", format!("Settings file is not valid JSON: {}", e));
+            return (Settings::default(), vec![problem]);
+        }
+    };
+
+    let mut problems = Vec::new();
+    if let Err(e) = migrate::migrate(&mut value) {
+        // Read what this build understands and leave the rest to defaults
+        problems.push(SettingsProblem::new("", e.to_string()));
+    }
+
+    let read = |value: &Value| {
+        serde_json::from_value::<Settings>(value.clone())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+    let mut resolved = layers.resolve(&value);
+    if read(&resolved).is_err() {
+        // Drop only the values of the wrong type, e.g. a string frame rate
+        for (key, error) in patch::drop_rejected(&mut resolved, read) {
+            let mut problem = SettingsProblem::new(&key, format!("Failed to read setting: {}", error));
+            problem.reset = true;
+            problems.push(problem);
+        }
+    }
+    let mut settings = match serde_json::from_value::<Settings>(resolved) {
+        Ok(settings) => settings,
+        Err(e) => {
+            problems.push(SettingsProblem::new("", format!("Failed to read settings: {}", e)));
+            return (Settings::default(), problems);
+        }
+    };
+    settings.version = SETTINGS_VERSION;
+    problems.extend(validate::repair(&mut settings));
+    (settings, problems)
+}
+
+/// Load settings from file, backing it up when it had to be repaired
+pub fn load_settings(layers: &ConfigLayers) -> (Settings, SettingsReport) {
     let path = get_settings_path();
-    if path.exists() {
-        match std::fs::read_to_string(&path) {
-            Ok(content) => {
-                match serde_json::from_str(&content) {
-                    Ok(settings) => return settings,
-                    Err(e) => eprintln!("Failed to parse settings: {}", e),
-                }
-            }
-            Err(e) => eprintln!("Failed to read settings file: {}", e),
+    if !path.exists() {
+        let (settings, problems) = parse_settings("{}", layers);
+        let problems = layers.problems.iter().cloned().chain(problems).collect();
+        return (settings, SettingsReport { problems, backup: None });
+    }
+
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::warn!("Failed to read settings file: {}", e);
+            let problem = SettingsProblem::new("", format!("Failed to read settings file: {}", e));
+            let report = SettingsReport { problems: vec![problem], backup: None };
+            return (Settings::default(), report);
         }
+    };
+
+    let (settings, problems) = parse_settings(&content, layers);
+    // A missing output directory is only reported, the file itself is fine
+    let backup = problems
+        .iter()
+        .any(|p| p.reset || p.key.is_empty())
+        .then(|| backup_settings(&path))
+        .flatten();
+    let problems: Vec<SettingsProblem> = layers.problems.iter().cloned().chain(problems).collect();
+    for problem in &problems {
+        tracing::warn!("Settings problem {}: {}", problem.key, problem.message);
     }
-    Settings::default()
+
+    (settings, SettingsReport { problems, backup })
 }
 
-/// Save settings to file
-pub fn save_settings(settings: &Settings) -> Result<(), String> {
+/// Save settings to file, keeping the previous versions as backups.
+///
+/// Values set by another layer are left as the file had them.
+pub fn save_settings(settings: &Settings, layers: &ConfigLayers) -> AppResult<()> {
     let path = get_settings_path();
     
     // Ensure directory exists
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            .map_err(|e| AppError::io("Failed to create config directory", parent, e))?;
     }
 
-    let content = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let on_disk = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+    let value = settings_value(settings)?;
+    let content = serde_json::to_string_pretty(&layers.user_document(&value, on_disk.as_ref()))
+        .map_err(|e| AppError::Other(format!("Failed to serialize settings: {}", e)))?;
 
-    std::fs::write(&path, content)
-        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+    if let Err(e) = file::rotate_backups(&path) {
+        tracing::warn!("Failed to back up settings: {}", e);
+    }
+    file::write_atomic(&path, content.as_bytes())
+        .map_err(|e| AppError::from_anyhow_at("Failed to write settings file", &path, e))?;
 
     Ok(())
 }
 
+fn settings_value(settings: &Settings) -> AppResult<Value> {
+    serde_json::to_value(settings).map_err(|e| AppError::Other(format!("Failed to serialize settings: {}", e)))
+}
+
+/// Settings as returned to the UI, with where each value comes from
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsView {
+    #[serde(flatten)]
+    pub settings: Settings,
+    /// Layer of each value, keyed like `output.frameRate`
+    pub sources: BTreeMap<String, Layer>,
+    /// Keys set by the system policy, the environment or the command line
+    pub locked: Vec<String>,
+}
+
 /// Get current settings
 #[tauri::command]
 pub async fn get_settings(
     settings: State<'_, SharedSettings>,
-) -> Result<Settings, String> {
-    let settings = settings.lock().await;
-    Ok(settings.clone())
+    layers: State<'_, ConfigLayers>,
+) -> AppResult<SettingsView> {
+    let settings = settings.lock().await.clone();
+    let value = settings_value(&settings)?;
+    Ok(SettingsView {
+        sources: layers.sources(&value),
+        locked: layers.locked_keys(),
+        settings,
+    })
+}
+
+/// Problems found loading the settings file at startup
+#[tauri::command]
+pub async fn get_settings_problems(
+    report: State<'_, SettingsReport>,
+) -> AppResult<SettingsReport> {
+    Ok(report.inner().clone())
+}
+
+/// Payload of the `settings-changed` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChanged {
+    pub settings: Settings,
+    /// Dotted paths of the changed values, e.g. `output.frameRate`
+    pub keys: Vec<String>,
+}
+
+/// Validate `new_settings` and make them current in `settings`, saving them
+/// unless they were just read from the file.
+///
+/// Emits `settings-changed` with the changed keys and returns them; nothing
+/// happens when no value changed.
+pub fn commit_settings(
+    app_handle: &AppHandle,
+    settings: &mut Settings,
+    new_settings: Settings,
+    save: bool,
+) -> AppResult<Vec<String>> {
+    let new_settings = Settings { version: SETTINGS_VERSION, ..new_settings };
+    let keys = patch::changed_keys(&settings_value(settings)?, &settings_value(&new_settings)?);
+    if keys.is_empty() {
+        return Ok(keys);
+    }
+    let layers = app_handle.state::<ConfigLayers>();
+    layers.check_unlocked(&keys)?;
+
+    // Only changed values are held against validation, so an unrelated
+    // problem such as an unmounted output drive doesn't block every edit.
+    // Problems the current settings don't have, like a hotkey clashing
+    // with the changed one, are new too.
+    let related = |a: &str, b: &str| a == b || a.starts_with(&format!("{}.", b));
+    let changed = |p: &SettingsProblem| keys.iter().any(|k| related(&p.key, k) || related(k, &p.key));
+    let existing = validate::validate(settings);
+    let problems: Vec<SettingsProblem> = validate::validate(&new_settings)
+        .into_iter()
+        .filter(|p| changed(p) || !existing.contains(p))
+        .collect();
+    if !problems.is_empty() {
+        return Err(AppError::InvalidSettings { problems });
+    }
+
+    // The same goes for the FFmpeg build, so settings made for another
+    // build can still be edited
+    if let Some(capabilities) = capabilities::cached(&app_handle.state::<SharedCapabilities>()) {
+        let problems: Vec<SettingsProblem> = validate::check_capabilities(&new_settings, &capabilities)
+            .into_iter()
+            .filter(changed)
+            .collect();
+        if !problems.is_empty() {
+            return Err(AppError::InvalidSettings { problems });
+        }
+    }
+
+    if settings.general.launch_at_startup != new_settings.general.launch_at_startup {
+        autostart::set_enabled(new_settings.general.launch_at_startup)
+            .map_err(|e| AppError::from_anyhow("Failed to update launch at startup", e))?;
+    }
+    if save {
+        save_settings(&new_settings, &layers)?;
+    }
+    profiles::sync_hotkeys(app_handle, &settings.profiles, &new_settings.profiles);
+    if settings.general.log_level != new_settings.general.log_level {
+        app_handle.state::<Logging>().set_level(new_settings.general.log_level);
+    }
+    *settings = new_settings;
+
+    let _ = app_handle.emit(
+        "settings-changed",
+        SettingsChanged {
+            settings: settings.clone(),
+            keys: keys.clone(),
+        },
+    );
+    Ok(keys)
 }
 
 /// Update settings
 #[tauri::command]
 pub async fn update_settings(
+    app_handle: AppHandle,
     new_settings: Settings,
     settings: State<'_, SharedSettings>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut settings = settings.lock().await;
-    *settings = new_settings.clone();
-    save_settings(&new_settings)?;
+    commit_settings(&app_handle, &mut settings, new_settings, true)?;
+    Ok(())
+}
+
+/// Read a settings document, failing on unknown keys and values that would
+/// have to be reset
+fn settings_from_value(value: Value) -> AppResult<Settings> {
+    let unknown = patch::unknown_keys(&value, &serde_json::to_value(Settings::default()).unwrap_or_default());
+    if !unknown.is_empty() {
+        let problems = unknown
+            .iter()
+            .map(|key| SettingsProblem::new(key, format!("Unknown setting: {}", key)))
+            .collect();
+        return Err(AppError::InvalidSettings { problems });
+    }
+    serde_json::from_value(value).map_err(|e| AppError::InvalidSettings {
+        problems: vec![SettingsProblem::new("", e.to_string())],
+    })
+}
+
+/// Change only the values in `patch`, a JSON merge patch such as
+/// `{"output": {"frameRate": 30}}`, returning the changed keys
+#[tauri::command]
+pub async fn patch_settings(
+    app_handle: AppHandle,
+    patch: Value,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Vec<String>> {
+    if !patch.is_object() {
+        return Err(AppError::Other(String::from("Settings patch must be a JSON object")));
+    }
+
+    let mut settings = settings.lock().await;
+    let mut value = settings_value(&settings)?;
+    patch::merge(&mut value, &patch);
+    let new_settings = settings_from_value(value)?;
+    commit_settings(&app_handle, &mut settings, new_settings, true)
+}
+
+/// Reset one section, e.g. `output` or `hotkeys`, to its defaults, returning
+/// the changed keys
+#[tauri::command]
+pub async fn reset_settings(
+    app_handle: AppHandle,
+    section: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Vec<String>> {
+    // Defaults as the system policy, environment and command line leave them
+    let (defaults, _) = parse_settings("{}", &app_handle.state::<ConfigLayers>());
+    let defaults = settings_value(&defaults)?;
+    let default_section = match section.as_str() {
+        "version" | "isFirstLaunch" => None,
+        section => defaults.get(section),
+    }
+    .ok_or_else(|| AppError::Other(format!("Unknown settings section: {}", section)))?;
+
+    let mut settings = settings.lock().await;
+    let mut value = settings_value(&settings)?;
+    value[section.as_str()] = default_section.clone();
+    let new_settings = settings_from_value(value)?;
+    commit_settings(&app_handle, &mut settings, new_settings, true)
+}
+
+/// Write the current settings to `path`
+#[tauri::command]
+pub async fn export_settings(
+    path: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(&*settings.lock().await)
+        .map_err(|e| AppError::Other(format!("Failed to serialize settings: {}", e)))?;
+    std::fs::write(&path, content).map_err(|e| AppError::io("Failed to export settings", path.as_ref(), e))
+}
+
+/// Replace the settings with the file at `path`, migrating it from older
+/// versions; returns the changed keys
+#[tauri::command]
+pub async fn import_settings(
+    app_handle: AppHandle,
+    path: String,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Vec<String>> {
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| AppError::io(&format!("Failed to read {}", path), path.as_ref(), e))?;
+    let (imported, problems) = parse_settings(&content, &app_handle.state::<ConfigLayers>());
+    if !problems.is_empty() {
+        return Err(AppError::InvalidSettings { problems });
+    }
+
+    let mut settings = settings.lock().await;
+    let imported = Settings {
+        is_first_launch: settings.is_first_launch,
+        ..imported
+    };
+    commit_settings(&app_handle, &mut settings, imported, true)
+}
+
+/// Preview a sound cue at the configured volume, with `file` instead of the
+/// configured sound when given
+#[tauri::command]
+pub async fn preview_sound_cue(
+    cue: Cue,
+    file: Option<String>,
+    settings: State<'_, SharedSettings>,
+    player: State<'_, SoundPlayer>,
+) -> AppResult<()> {
+    let sounds = settings.lock().await.recording.sounds.clone();
+    let file = match file {
+        Some(file) if !std::path::Path::new(&file).is_file() => {
+            return Err(AppError::NotFound { path: PathBuf::from(file) });
+        }
+        Some(file) => Some(PathBuf::from(file)),
+        None => sounds.file(cue),
+    };
+
+    player.play(CueSound {
+        cue,
+        file,
+        volume: sounds.volume,
+    });
     Ok(())
 }
 
@@ -177,20 +659,57 @@ pub async fn update_settings(
 #[tauri::command]
 pub async fn get_output_directory(
     settings: State<'_, SharedSettings>,
-) -> Result<String, String> {
+) -> AppResult<String> {
     let settings = settings.lock().await;
     Ok(settings.output.directory.clone())
 }
 
-/// Select output directory using file dialog
+/// What happens to the recordings in the previous output directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PreviousRecordings {
+    /// Leave them where they are
+    #[default]
+    Keep,
+    /// Move them into the new directory
+    Move,
+    /// Leave them in place and keep listing them in the library
+    Index,
+}
+
+/// Result of choosing a new output directory
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputDirectoryChanged {
+    pub directory: String,
+    /// Files moved from the previous directory
+    pub moved: usize,
+    /// Files that couldn't be moved, and why
+    pub errors: Vec<String>,
+}
+
+/// Fail unless files can be created in `dir`
+fn ensure_writable(dir: &std::path::Path) -> AppResult<()> {
+    let probe = dir.join(format!(".flashscreen-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| AppError::io("Folder is not writable", dir, e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Select output directory using file dialog and save it in the settings,
+/// handling the recordings in the previous directory as `previous` says.
+///
+/// Returns `None` when the dialog was cancelled.
 #[tauri::command]
 pub async fn select_output_directory(
-    app: tauri::AppHandle,
-) -> Result<Option<String>, String> {
-    use tauri_plugin_dialog::{DialogExt, FilePath};
-    use std::sync::mpsc;
+    app: AppHandle,
+    previous: Option<PreviousRecordings>,
+    settings: State<'_, SharedSettings>,
+) -> AppResult<Option<OutputDirectoryChanged>> {
+    use tauri_plugin_dialog::DialogExt;
+    use tokio::sync::oneshot;
 
-    let (tx, rx) = mpsc::channel::<Option<FilePath>>();
+    let (tx, rx) = oneshot::channel();
 
     app.dialog()
         .file()
@@ -199,10 +718,41 @@ pub async fn select_output_directory(
             let _ = tx.send(folder_path);
         });
 
-    // Wait for the dialog result
-    match rx.recv() {
-        Ok(Some(path)) => Ok(Some(path.to_string())),
-        Ok(None) => Ok(None),
-        Err(_) => Ok(None),
+    // Awaited, so the runtime keeps running while the dialog is open
+    let Some(folder) = rx.await.ok().flatten() else {
+        return Ok(None);
+    };
+    let directory = folder.into_path().map_err(|e| AppError::Other(format!("Invalid folder: {}", e)))?;
+    ensure_writable(&directory)?;
+
+    let previous = previous.unwrap_or_default();
+    let mut settings = settings.lock().await;
+    let old = settings.output.directory_path();
+    let changed = old.exists() && old.canonicalize().ok() != directory.canonicalize().ok();
+
+    let mut new_settings = settings.clone();
+    let output = &mut new_settings.output;
+    output.directory = directory.to_string_lossy().to_string();
+    output.library_directories.retain(|d| std::path::Path::new(d) != directory);
+    let old_directory = old.to_string_lossy().to_string();
+    if changed && previous == PreviousRecordings::Index && !output.library_directories.contains(&old_directory) {
+        output.library_directories.push(old_directory);
+    }
+    commit_settings(&app, &mut settings, new_settings, true)?;
+    drop(settings);
+
+    let (moved, errors) = if changed && previous == PreviousRecordings::Move {
+        let target = directory.clone();
+        tauri::async_runtime::spawn_blocking(move || files::relocate_recordings(&old, &target))
+            .await
+            .map_err(|e| AppError::Other(format!("Failed to move recordings: {}", e)))?
+    } else {
+        (0, Vec::new())
+    };
+
+    let directory = directory.to_string_lossy().to_string();
+    if changed {
+        let _ = app.emit("recordings-changed", vec![directory.clone()]);
     }
+    Ok(Some(OutputDirectoryChanged { directory, moved, errors }))
 }
@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// How many earlier versions of the settings file are kept
+pub const BACKUP_COUNT: usize = 5;
+
+/// `settings.json.bak.<n>`, 1 being the most recent
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("{}.bak.{}", name, n))
+}
+
+/// Shift the backups of `path` by one and copy it to the first slot,
+/// dropping the oldest
+pub fn rotate_backups(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            std::fs::rename(&from, backup_path(path, n + 1))
+                .with_context(|| format!("Failed to rotate {}", from.display()))?;
+        }
+    }
+    std::fs::copy(path, backup_path(path, 1)).with_context(|| format!("Failed to back up {}", path.display()))?;
+    Ok(())
+}
+
+/// Write `contents` to `path` so it either keeps its old contents or has the
+/// new ones in full, even if the process dies halfway.
+///
+/// The data goes to a temp file in the same directory, is flushed to disk,
+/// and then renamed over `path`.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let result = (|| {
+        let mut file = File::create(&temp).with_context(|| format!("Failed to create {}", temp.display()))?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&temp, path).with_context(|| format!("Failed to replace {}", path.display()))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result?;
+
+    // Persist the rename itself; directories can't be opened for this on Windows
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
@@ -0,0 +1,379 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::migrate;
+use super::patch;
+use super::validate::SettingsProblem;
+use crate::commands::settings::Settings;
+use crate::error::{AppError, AppResult};
+
+/// Prefix of environment variables overriding settings, e.g.
+/// `FLASHSCREEN_OUTPUT_DIRECTORY` for `output.directory`
+const ENV_PREFIX: &str = "FLASHSCREEN_";
+
+/// Where a setting's value comes from, from lowest to highest precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Layer {
+    /// Built into the app
+    Default,
+    /// The system-wide settings file
+    System,
+    /// The user's `settings.json`
+    User,
+    /// A `FLASHSCREEN_*` environment variable
+    Env,
+    /// A `--set key=value` command line flag
+    Cli,
+}
+
+impl Layer {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Layer::Default => "the defaults",
+            Layer::System => "the system policy",
+            Layer::User => "the user settings",
+            Layer::Env => "an environment variable",
+            Layer::Cli => "a command line flag",
+        }
+    }
+}
+
+/// The configuration layers around the user's settings file.
+///
+/// Values are taken from the defaults, then the system file, the user file,
+/// the environment and the command line, each overriding the ones before.
+/// Keys listed under `locked` in the system file, and keys set through the
+/// environment or the command line, can't be changed by the user.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayers {
+    /// Values of the system file
+    system: Value,
+    /// Keys of the system file users can't change
+    locked: Vec<String>,
+    env: Vec<(String, Value)>,
+    cli: Vec<(String, Value)>,
+    /// What went wrong reading the layers
+    pub problems: Vec<SettingsProblem>,
+}
+
+/// System-wide settings file, e.g. `/etc/flashscreen/settings.json`
+fn system_settings_path() -> PathBuf {
+    #[cfg(windows)]
+    {
+        std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+            .join("FlashScreen")
+            .join("settings.json")
+    }
+    #[cfg(not(windows))]
+    {
+        PathBuf::from("/etc/flashscreen/settings.json")
+    }
+}
+
+/// `/output/encoderProfile` for `output.encoderProfile`
+fn pointer(key: &str) -> String {
+    format!("/{}", key.replace('.', "/"))
+}
+
+/// Set `key` in `value`, creating the sections on the way
+fn set_key(value: &mut Value, key: &str, new: Value) {
+    let mut current = value;
+    for part in key.split('.') {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        let Value::Object(object) = current else {
+            return;
+        };
+        current = object.entry(part).or_insert(Value::Null);
+    }
+    *current = new;
+}
+
+fn remove_key(value: &mut Value, key: &str) {
+    let Some((parent, last)) = key.rsplit_once('.') else {
+        if let Value::Object(object) = value {
+            object.remove(key);
+        }
+        return;
+    };
+    if let Some(Value::Object(object)) = value.pointer_mut(&pointer(parent)) {
+        object.remove(last);
+    }
+}
+
+/// Dotted paths of the values in `value`; lists count as one value
+fn leaf_keys(value: &Value, prefix: &str, keys: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                leaf_keys(child, &path, keys);
+            }
+        }
+        _ => keys.push(prefix.to_string()),
+    }
+}
+
+/// `FLASHSCREEN_OUTPUT_ENCODER_PROFILE` for `output.encoderProfile`
+fn env_name(key: &str) -> String {
+    let mut name = String::from(ENV_PREFIX);
+    for (i, part) in key.split('.').enumerate() {
+        if i > 0 {
+            name.push('_');
+        }
+        for c in part.chars() {
+            if c.is_uppercase() {
+                name.push('_');
+            }
+            name.push(c.to_ascii_uppercase());
+        }
+    }
+    name
+}
+
+/// Read `raw` as JSON, or as text for settings holding text
+fn parse_raw(raw: &str, default: Option<&Value>) -> Value {
+    match default {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        _ => serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string())),
+    }
+}
+
+/// Whether `key` is `locked` or one of the values below it
+fn covers(locked: &str, key: &str) -> bool {
+    key == locked || key.starts_with(&format!("{}.", locked))
+}
+
+impl ConfigLayers {
+    /// Read the system file, the environment and the command line
+    pub fn load() -> Self {
+        let defaults = serde_json::to_value(Settings::default()).unwrap_or_default();
+        let mut known = Vec::new();
+        leaf_keys(&defaults, "", &mut known);
+        known.retain(|k| k != "version");
+
+        let mut layers = Self {
+            system: Value::Object(Map::new()),
+            ..Self::default()
+        };
+        layers.load_system(&known);
+
+        for key in &known {
+            if let Ok(raw) = std::env::var(env_name(key)) {
+                let value = parse_raw(&raw, defaults.pointer(&pointer(key)));
+                layers.env.push((key.clone(), value));
+            }
+        }
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let assignment = match arg.strip_prefix("--set=") {
+                Some(assignment) => Some(assignment.to_string()),
+                None if arg == "--set" => args.next(),
+                None => continue,
+            };
+            let Some((key, raw)) = assignment.as_deref().and_then(|a| a.split_once('=')) else {
+                layers.problems.push(SettingsProblem::new("", format!("Expected --set key=value, got {}", arg)));
+                continue;
+            };
+            if !known.iter().any(|k| k == key) {
+                layers.problems.push(SettingsProblem::new(key, format!("Unknown setting on the command line: {}", key)));
+                continue;
+            }
+            let value = parse_raw(raw, defaults.pointer(&pointer(key)));
+            layers.cli.push((key.to_string(), value));
+        }
+
+        layers
+    }
+
+    fn load_system(&mut self, known: &[String]) {
+        let path = system_settings_path();
+        if !path.exists() {
+            return;
+        }
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Value>(&content).map_err(|e| e.to_string()));
+        let mut system = match parsed {
+            Ok(system) => system,
+            Err(e) => {
+                let message = format!("Ignoring system settings {}: {}", path.display(), e);
+                self.problems.push(SettingsProblem::new("", message));
+                return;
+            }
+        };
+
+        let locked = system.as_object_mut().and_then(|o| o.remove("locked"));
+        if let Err(e) = migrate::migrate(&mut system) {
+            let message = format!("Ignoring system settings {}: {}", path.display(), e);
+            self.problems.push(SettingsProblem::new("", message));
+            return;
+        }
+        if let Value::Object(object) = &mut system {
+            object.remove("version");
+        }
+
+        for key in locked.as_ref().and_then(Value::as_array).into_iter().flatten() {
+            match key.as_str() {
+                Some(key) if known.iter().any(|k| covers(key, k)) => self.locked.push(key.to_string()),
+                _ => self.problems.push(SettingsProblem::new("", format!("Unknown locked setting {} in {}", key, path.display()))),
+            }
+        }
+        self.system = system;
+    }
+
+    /// Settings document combining all layers with the user's `user` document
+    pub fn resolve(&self, user: &Value) -> Value {
+        let mut value = self.system.clone();
+        patch::merge(&mut value, user);
+
+        for key in &self.locked {
+            match self.system.pointer(&pointer(key)) {
+                Some(locked) => set_key(&mut value, key, locked.clone()),
+                // Locked without a value keeps the default
+                None => remove_key(&mut value, key),
+            }
+        }
+        for (key, override_value) in self.env.iter().chain(&self.cli) {
+            set_key(&mut value, key, override_value.clone());
+        }
+        value
+    }
+
+    /// Layer that keeps `key` from being changed, if any
+    pub fn locked_by(&self, key: &str) -> Option<Layer> {
+        if self.cli.iter().any(|(k, _)| covers(k, key)) {
+            Some(Layer::Cli)
+        } else if self.env.iter().any(|(k, _)| covers(k, key)) {
+            Some(Layer::Env)
+        } else if self.locked.iter().any(|k| covers(k, key)) {
+            Some(Layer::System)
+        } else {
+            None
+        }
+    }
+
+    /// Keys the user can't change
+    pub fn locked_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .locked
+            .iter()
+            .chain(self.env.iter().chain(&self.cli).map(|(k, _)| k))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Fail if `keys` include one the user can't change
+    pub fn check_unlocked(&self, keys: &[String]) -> AppResult<()> {
+        match keys.iter().find_map(|k| self.locked_by(k).map(|layer| (k, layer))) {
+            Some((key, layer)) => Err(AppError::SettingLocked { key: key.clone(), layer }),
+            None => Ok(()),
+        }
+    }
+
+    /// Layer each value of `settings` comes from
+    pub fn sources(&self, settings: &Value) -> BTreeMap<String, Layer> {
+        let mut base = serde_json::to_value(Settings::default()).unwrap_or_default();
+        patch::merge(&mut base, &self.system);
+
+        let mut keys = Vec::new();
+        leaf_keys(settings, "", &mut keys);
+        keys.into_iter()
+            .map(|key| {
+                let ptr = pointer(&key);
+                let layer = self.locked_by(&key).unwrap_or_else(|| {
+                    if settings.pointer(&ptr) != base.pointer(&ptr) {
+                        Layer::User
+                    } else if self.system.pointer(&ptr).is_some() {
+                        Layer::System
+                    } else {
+                        Layer::Default
+                    }
+                });
+                (key, layer)
+            })
+            .collect()
+    }
+
+    /// What to write to the user's file for `settings`: locked values keep
+    /// what the file had, so forced values don't stick once the force is lifted
+    pub fn user_document(&self, settings: &Value, on_disk: Option<&Value>) -> Value {
+        let mut document = settings.clone();
+        for key in self.locked_keys() {
+            match on_disk.and_then(|d| d.pointer(&pointer(&key))) {
+                Some(previous) => set_key(&mut document, &key, previous.clone()),
+                None => remove_key(&mut document, &key),
+            }
+        }
+        document
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn env_names_split_camel_case() {
+        assert_eq!(env_name("output.encoderProfile"), "FLASHSCREEN_OUTPUT_ENCODER_PROFILE");
+        assert_eq!(env_name("general.logLevel"), "FLASHSCREEN_GENERAL_LOG_LEVEL");
+        assert_eq!(env_name("isFirstLaunch"), "FLASHSCREEN_IS_FIRST_LAUNCH");
+    }
+
+    fn layers() -> ConfigLayers {
+        ConfigLayers {
+            system: json!({ "output": { "frameRate": 30, "format": "mkv" } }),
+            locked: vec![String::from("output.frameRate"), String::from("output.resolution")],
+            env: vec![(String::from("output.directory"), json!("/env"))],
+            cli: vec![(String::from("output.directory"), json!("/cli"))],
+            problems: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn resolve_applies_the_layers_in_order() {
+        let user = json!({ "output": { "frameRate": 60, "format": "mp4", "resolution": "720p", "directory": "/user" } });
+        let resolved = layers().resolve(&user);
+        // Locked keys keep the system value, or the default when it has none
+        assert_eq!(resolved["output"]["frameRate"], 30);
+        assert!(resolved["output"].get("resolution").is_none());
+        // Unlocked system values are only defaults for the user's
+        assert_eq!(resolved["output"]["format"], "mp4");
+        // The command line wins over the environment
+        assert_eq!(resolved["output"]["directory"], "/cli");
+    }
+
+    #[test]
+    fn resolve_keeps_system_values_the_user_did_not_set() {
+        let resolved = layers().resolve(&json!({}));
+        assert_eq!(resolved["output"]["format"], "mkv");
+    }
+
+    #[test]
+    fn user_document_keeps_what_the_file_had_for_locked_keys() {
+        let settings = json!({ "output": { "frameRate": 30, "format": "mp4", "directory": "/cli", "resolution": "1080p" } });
+        let on_disk = json!({ "output": { "frameRate": 60, "directory": "/user" } });
+        let document = layers().user_document(&settings, Some(&on_disk));
+        assert_eq!(
+            document,
+            json!({ "output": { "frameRate": 60, "format": "mp4", "directory": "/user" } })
+        );
+    }
+
+    #[test]
+    fn user_document_leaves_out_locked_keys_without_a_file() {
+        let settings = json!({ "output": { "frameRate": 30, "format": "mp4" } });
+        assert_eq!(layers().user_document(&settings, None), json!({ "output": { "format": "mp4" } }));
+    }
+}
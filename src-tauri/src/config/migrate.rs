@@ -0,0 +1,116 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// Schema version written by this build
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// `MIGRATIONS[n - 1]` turns a version `n` document into version `n + 1`
+const MIGRATIONS: &[fn(&mut Value)] = &[v1_to_v2];
+
+/// Files written before the version field existed.
+///
+/// Sections saved as `null` are dropped so their defaults apply, and
+/// resolution names are brought to the ones the recorder knows.
+fn v1_to_v2(value: &mut Value) {
+    if let Some(object) = value.as_object_mut() {
+        object.retain(|_, section| !section.is_null());
+    }
+
+    if let Some(resolution) = value.pointer_mut("/output/resolution") {
+        let normalized = match resolution.as_str().map(|r| r.trim().to_lowercase()) {
+            Some(r) if r == "2160p" || r == "uhd" => String::from("4k"),
+            Some(r) if r == "source" || r.is_empty() => String::from("original"),
+            Some(r) => r,
+            None => return,
+        };
+        *resolution = Value::String(normalized);
+    }
+}
+
+/// Version of a settings document; files without one are version 1.
+///
+/// Versions too large for a `u32` count as the newest possible one.
+pub fn version_of(value: &Value) -> u32 {
+    value
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(1, |v| u32::try_from(v).unwrap_or(u32::MAX).max(1))
+}
+
+/// Bring a settings document up to `SETTINGS_VERSION`, returning the version
+/// it had.
+///
+/// Fails for documents written by a newer build, which this one can only
+/// read partially.
+pub fn migrate(value: &mut Value) -> Result<u32> {
+    if !value.is_object() {
+        return Err(anyhow!("Settings must be a JSON object"));
+    }
+
+    let version = version_of(value);
+    if version > SETTINGS_VERSION {
+        return Err(anyhow!(
+            "Settings were written by a newer version of FlashScreen (schema {}, this build reads {})",
+            version,
+            SETTINGS_VERSION
+        ));
+    }
+
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        migration(value);
+    }
+    value["version"] = Value::from(SETTINGS_VERSION);
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn documents_without_a_version_are_version_1() {
+        assert_eq!(version_of(&json!({})), 1);
+        assert_eq!(version_of(&json!({ "version": 0 })), 1);
+        assert_eq!(version_of(&json!({ "version": 2 })), 2);
+    }
+
+    #[test]
+    fn migrates_version_1_documents() {
+        let mut value = json!({
+            "output": { "resolution": " UHD " },
+            "camera": null,
+        });
+        assert_eq!(migrate(&mut value).unwrap(), 1);
+        assert_eq!(value, json!({ "output": { "resolution": "4k" }, "version": SETTINGS_VERSION }));
+    }
+
+    #[test]
+    fn normalizes_source_resolutions() {
+        for resolution in ["source", ""] {
+            let mut value = json!({ "output": { "resolution": resolution } });
+            migrate(&mut value).unwrap();
+            assert_eq!(value["output"]["resolution"], "original");
+        }
+    }
+
+    #[test]
+    fn leaves_current_documents_alone() {
+        let mut value = json!({ "version": SETTINGS_VERSION, "output": { "resolution": "source" } });
+        assert_eq!(migrate(&mut value).unwrap(), SETTINGS_VERSION);
+        assert_eq!(value["output"]["resolution"], "source");
+    }
+
+    #[test]
+    fn rejects_newer_and_non_object_documents() {
+        assert!(migrate(&mut json!({ "version": SETTINGS_VERSION + 1 })).is_err());
+        assert!(migrate(&mut json!([])).is_err());
+    }
+
+    #[test]
+    fn rejects_versions_too_large_for_u32() {
+        let mut value = json!({ "version": 4294967296u64 });
+        assert_eq!(version_of(&value), u32::MAX);
+        assert!(migrate(&mut value).is_err());
+    }
+}
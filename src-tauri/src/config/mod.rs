@@ -0,0 +1,6 @@
+pub mod file;
+pub mod layers;
+pub mod migrate;
+pub mod patch;
+pub mod validate;
+pub mod watch;
@@ -0,0 +1,246 @@
+use serde_json::{Map, Value};
+
+/// Apply a JSON merge patch (RFC 7396): objects are merged key by key, `null`
+/// removes a key and anything else replaces the target value
+pub fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        return;
+    };
+
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge(target.entry(key.as_str()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn collect_changes(prefix: &str, old: &Value, new: &Value, keys: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, value) in new {
+                let path = join(prefix, key);
+                match old.get(key) {
+                    Some(previous) => collect_changes(&path, previous, value, keys),
+                    None => keys.push(path),
+                }
+            }
+            for key in old.keys().filter(|k| !new.contains_key(*k)) {
+                keys.push(join(prefix, key));
+            }
+        }
+        // Lists and plain values count as one setting
+        _ if old != new => keys.push(prefix.to_string()),
+        _ => {}
+    }
+}
+
+/// Dotted paths of the values that differ between `old` and `new`, e.g.
+/// `output.frameRate`
+pub fn changed_keys(old: &Value, new: &Value) -> Vec<String> {
+    let mut keys = Vec::new();
+    collect_changes("", old, new, &mut keys);
+    keys
+}
+
+fn collect_unknown(prefix: &str, value: &Value, known: &Value, keys: &mut Vec<String>) {
+    let (Value::Object(value), Value::Object(known)) = (value, known) else {
+        return;
+    };
+    for (key, child) in value {
+        let path = join(prefix, key);
+        match known.get(key) {
+            Some(reference) => collect_unknown(&path, child, reference, keys),
+            None => keys.push(path),
+        }
+    }
+}
+
+/// Keys of `value` missing from `known`, i.e. ones that would be dropped
+/// when `value` is read into a struct serializing to `known`
+pub fn unknown_keys(value: &Value, known: &Value) -> Vec<String> {
+    let mut keys = Vec::new();
+    collect_unknown("", value, known, &mut keys);
+    keys
+}
+
+/// Step from a value to one of its children
+enum Step {
+    Key(String),
+    Item,
+}
+
+/// `value` wrapped in the objects and lists leading to it, so it can be
+/// read without the rest of the document
+fn isolate(steps: &[Step], value: &Value) -> Value {
+    steps.iter().rev().fold(value.clone(), |inner, step| match step {
+        Step::Key(key) => Value::Object(Map::from_iter([(key.clone(), inner)])),
+        // Items are read the same wherever they are in the list
+        Step::Item => Value::Array(vec![inner]),
+    })
+}
+
+/// Drop the parts of `value` that `read` rejects, returning whether
+/// anything of it is left
+fn collect_rejected<F>(
+    prefix: &str,
+    steps: &mut Vec<Step>,
+    value: &mut Value,
+    read: &F,
+    rejected: &mut Vec<(String, String)>,
+) -> bool
+where
+    F: Fn(&Value) -> Result<(), String>,
+{
+    let Err(error) = read(&isolate(steps, value)) else {
+        return true;
+    };
+
+    // Look for the culprits among the children first
+    match value {
+        Value::Object(object) => {
+            object.retain(|key, child| {
+                steps.push(Step::Key(key.clone()));
+                let keep = collect_rejected(&join(prefix, key), steps, child, read, rejected);
+                steps.pop();
+                keep
+            });
+        }
+        Value::Array(items) => {
+            let mut index = 0;
+            items.retain_mut(|item| {
+                steps.push(Step::Item);
+                let keep = collect_rejected(&join(prefix, &index.to_string()), steps, item, read, rejected);
+                steps.pop();
+                index += 1;
+                keep
+            });
+        }
+        _ => {}
+    }
+    if (value.is_object() || value.is_array()) && read(&isolate(steps, value)).is_ok() {
+        return true;
+    }
+
+    rejected.push((prefix.to_string(), error));
+    false
+}
+
+/// Remove the values of `value` that `read` rejects, each checked on its
+/// own so one bad value doesn't cost the rest. Returns the dotted paths
+/// of what was removed with the reason, e.g. `output.frameRate`; list
+/// items are numbered, e.g. `profiles.0.hotkey`.
+pub fn drop_rejected<F>(value: &mut Value, read: F) -> Vec<(String, String)>
+where
+    F: Fn(&Value) -> Result<(), String>,
+{
+    let mut rejected = Vec::new();
+    if !collect_rejected("", &mut Vec::new(), value, &read, &mut rejected) {
+        *value = Value::Object(Map::new());
+    }
+    rejected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[test]
+    fn merge_replaces_values_and_removes_nulls() {
+        let mut target = json!({ "output": { "frameRate": 30, "format": "mp4" }, "profiles": [1, 2] });
+        merge(&mut target, &json!({ "output": { "frameRate": 60, "format": null }, "profiles": [3] }));
+        assert_eq!(target, json!({ "output": { "frameRate": 60 }, "profiles": [3] }));
+    }
+
+    #[test]
+    fn merge_turns_values_into_objects_when_patched_with_one() {
+        let mut target = json!({ "output": 1 });
+        merge(&mut target, &json!({ "output": { "frameRate": 60 } }));
+        assert_eq!(target, json!({ "output": { "frameRate": 60 } }));
+    }
+
+    #[test]
+    fn changed_keys_lists_changed_added_and_removed_values() {
+        let old = json!({ "output": { "frameRate": 30, "format": "mp4" }, "profiles": [1] });
+        let new = json!({ "output": { "frameRate": 60, "resolution": "1080p" }, "profiles": [1] });
+        let mut keys = changed_keys(&old, &new);
+        keys.sort();
+        assert_eq!(keys, ["output.format", "output.frameRate", "output.resolution"]);
+    }
+
+    #[test]
+    fn changed_keys_counts_lists_as_one_value() {
+        let keys = changed_keys(&json!({ "profiles": [1, 2] }), &json!({ "profiles": [1, 3] }));
+        assert_eq!(keys, ["profiles"]);
+    }
+
+    #[test]
+    fn unknown_keys_lists_keys_missing_from_the_reference() {
+        let value = json!({ "output": { "frameRate": 60, "fps": 60 }, "theme": "dark" });
+        let known = json!({ "output": { "frameRate": 30 } });
+        assert_eq!(unknown_keys(&value, &known), ["output.fps", "theme"]);
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(default)]
+    #[allow(dead_code)]
+    struct Document {
+        rate: u32,
+        name: String,
+        items: Vec<Item>,
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(default)]
+    #[allow(dead_code)]
+    struct Item {
+        size: u32,
+    }
+
+    fn read(value: &Value) -> Result<(), String> {
+        serde_json::from_value::<Document>(value.clone()).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn drop_rejected_keeps_valid_values() {
+        let mut value = json!({ "rate": "fast", "name": "ok", "items": [{ "size": 1 }, { "size": "big" }] });
+        let mut rejected: Vec<String> = drop_rejected(&mut value, read).into_iter().map(|(key, _)| key).collect();
+        rejected.sort();
+        assert_eq!(rejected, ["items.1.size", "rate"]);
+        assert_eq!(value, json!({ "name": "ok", "items": [{ "size": 1 }, {}] }));
+        assert!(read(&value).is_ok());
+    }
+
+    #[test]
+    fn drop_rejected_drops_items_of_the_wrong_shape() {
+        let mut value = json!({ "items": [{ "size": 1 }, 5] });
+        let rejected: Vec<String> = drop_rejected(&mut value, read).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(rejected, ["items.1"]);
+        assert_eq!(value, json!({ "items": [{ "size": 1 }] }));
+    }
+
+    #[test]
+    fn drop_rejected_empties_documents_that_are_not_objects() {
+        let mut value = json!("settings");
+        assert_eq!(drop_rejected(&mut value, read).len(), 1);
+        assert_eq!(value, json!({}));
+    }
+}
@@ -0,0 +1,271 @@
+use serde::Serialize;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use tauri_plugin_global_shortcut::Shortcut;
+
+use crate::commands::settings::{OutputSettings, Settings};
+use crate::media::capabilities::FfmpegCapabilities;
+use crate::media::profile::EncoderProfile;
+use crate::recording::profiles::RecordingProfile;
+use crate::recording::state::RecordingMode;
+
+/// Resolutions the recorder can scale to; `original` keeps the screen size
+pub const RESOLUTIONS: &[&str] = &["original", "720p", "1080p", "1440p", "4k"];
+
+pub const FRAME_RATES: RangeInclusive<u32> = 1..=240;
+
+/// Something wrong with one setting
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsProblem {
+    /// Path of the setting, e.g. `output.frameRate`; empty for the whole file
+    pub key: String,
+    pub message: String,
+    /// The value was replaced by its default
+    pub reset: bool,
+}
+
+impl SettingsProblem {
+    pub fn new(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            message: message.into(),
+            reset: false,
+        }
+    }
+}
+
+/// Problems that make `settings` unusable as they are
+pub fn validate(settings: &Settings) -> Vec<SettingsProblem> {
+    let mut problems = Vec::new();
+    let output = &settings.output;
+
+    if !FRAME_RATES.contains(&output.frame_rate) {
+        problems.push(SettingsProblem::new(
+            "output.frameRate",
+            format!(
+                "Frame rate must be between {} and {} fps, got {}",
+                FRAME_RATES.start(),
+                FRAME_RATES.end(),
+                output.frame_rate
+            ),
+        ));
+    }
+    if !RESOLUTIONS.contains(&output.resolution.as_str()) {
+        problems.push(SettingsProblem::new(
+            "output.resolution",
+            format!("Unknown resolution \"{}\", expected one of {}", output.resolution, RESOLUTIONS.join(", ")),
+        ));
+    }
+    // The default Videos/FlashScreen, also used when empty, is created on demand
+    let default_directory = output.directory.is_empty() || output.directory == OutputSettings::default().directory;
+    if !default_directory && !Path::new(&output.directory).is_dir() {
+        problems.push(SettingsProblem::new(
+            "output.directory",
+            format!("Output directory {} does not exist", output.directory),
+        ));
+    }
+    if EncoderProfile::find(&output.encoder_profile).is_none() {
+        problems.push(SettingsProblem::new(
+            "output.encoderProfile",
+            format!("Unknown encoder profile \"{}\"", output.encoder_profile),
+        ));
+    }
+    if output.export_concurrency == 0 {
+        problems.push(SettingsProblem::new("output.exportConcurrency", "At least one export must be able to run"));
+    }
+
+    let mut hotkeys = vec![
+        (String::from("hotkeys.startStop"), &settings.hotkeys.start_stop),
+        (String::from("hotkeys.pauseResume"), &settings.hotkeys.pause_resume),
+        (String::from("hotkeys.cancel"), &settings.hotkeys.cancel),
+        (String::from("hotkeys.toggleCamera"), &settings.hotkeys.toggle_camera),
+    ];
+    hotkeys.extend(
+        settings
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (format!("profiles.{}.hotkey", i), &p.hotkey)),
+    );
+    for (i, (key, hotkey)) in hotkeys.iter().enumerate() {
+        // Empty leaves the action without a hotkey
+        if hotkey.is_empty() {
+            continue;
+        }
+        let Ok(shortcut) = hotkey.parse::<Shortcut>() else {
+            problems.push(SettingsProblem::new(key, format!("\"{}\" is not a valid hotkey", hotkey)));
+            continue;
+        };
+        let taken = hotkeys[..i]
+            .iter()
+            .find(|(_, other)| other.parse::<Shortcut>().is_ok_and(|s| s == shortcut));
+        if let Some((other, _)) = taken {
+            problems.push(SettingsProblem::new(key, format!("{} is already used by {}", hotkey, other)));
+        }
+    }
+
+    for (i, profile) in settings.profiles.iter().enumerate() {
+        let key = |field: &str| format!("profiles.{}.{}", i, field);
+        if profile.name.trim().is_empty() {
+            problems.push(SettingsProblem::new(&key("name"), "Recording profiles need a name"));
+        }
+        if settings.profiles[..i].iter().any(|p| p.id == profile.id) {
+            problems.push(SettingsProblem::new(&key("id"), format!("Profile id \"{}\" is used twice", profile.id)));
+        }
+        if profile.mode == RecordingMode::Region && profile.region.is_none() {
+            problems.push(SettingsProblem::new(&key("region"), format!("{} records a region but has none", profile.name)));
+        }
+        if !FRAME_RATES.contains(&profile.frame_rate) {
+            problems.push(SettingsProblem::new(
+                &key("frameRate"),
+                format!("{}: frame rate must be between {} and {} fps", profile.name, FRAME_RATES.start(), FRAME_RATES.end()),
+            ));
+        }
+        if !RESOLUTIONS.contains(&profile.resolution.as_str()) {
+            problems.push(SettingsProblem::new(
+                &key("resolution"),
+                format!("{}: unknown resolution \"{}\"", profile.name, profile.resolution),
+            ));
+        }
+        if EncoderProfile::find(&profile.encoder_profile).is_none() {
+            problems.push(SettingsProblem::new(
+                &key("encoderProfile"),
+                format!("{}: unknown encoder profile \"{}\"", profile.name, profile.encoder_profile),
+            ));
+        }
+    }
+
+    if !(0.0..=1.0).contains(&settings.recording.sounds.volume) {
+        problems.push(SettingsProblem::new("recording.sounds.volume", "Volume must be between 0 and 1"));
+    }
+
+    problems
+}
+
+/// Problems with what the FFmpeg build can't do: encoder profiles needing
+/// encoders or muxers it lacks, and audio processing needing missing filters
+pub fn check_capabilities(settings: &Settings, capabilities: &FfmpegCapabilities) -> Vec<SettingsProblem> {
+    let mut problems = Vec::new();
+    let mut check_profile = |key: String, id: &str, owner: &str| {
+        let missing = EncoderProfile::find(id).map(|p| capabilities.missing(&p)).unwrap_or_default();
+        if !missing.is_empty() {
+            problems.push(SettingsProblem::new(
+                &key,
+                format!("{}encoder profile \"{}\" needs {}, which this FFmpeg build lacks", owner, id, missing.join(", ")),
+            ));
+        }
+    };
+    check_profile(String::from("output.encoderProfile"), &settings.output.encoder_profile, "");
+    for (i, profile) in settings.profiles.iter().enumerate() {
+        let owner = format!("{}: ", profile.name);
+        check_profile(format!("profiles.{}.encoderProfile", i), &profile.encoder_profile, &owner);
+    }
+
+    let audio = [
+        ("audio.microphone", &settings.audio.microphone),
+        ("audio.systemAudio", &settings.audio.system_audio),
+    ];
+    for (key, processing) in audio {
+        let Ok(Some(chain)) = processing.filter(processing.mode) else {
+            continue;
+        };
+        let missing = capabilities.missing_filters(&chain);
+        if !missing.is_empty() {
+            problems.push(SettingsProblem::new(
+                key,
+                format!("Audio processing needs {}, which this FFmpeg build lacks", missing.join(", ")),
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Validate `settings` and reset what can't be used to its default.
+///
+/// The output directory is only reported, since it may be a drive that
+/// isn't mounted right now.
+pub fn repair(settings: &mut Settings) -> Vec<SettingsProblem> {
+    let mut problems: Vec<SettingsProblem> = Vec::new();
+
+    // A default can clash with another value, e.g. a hotkey the user gave
+    // to a profile, so validate again until nothing changes
+    loop {
+        let mut changed = false;
+        for mut problem in validate(settings) {
+            match problems.iter_mut().find(|p| p.key == problem.key) {
+                // The default didn't help, leave the action without a hotkey
+                Some(reported) if reported.reset => {
+                    if clear_hotkey(settings, &problem.key) {
+                        reported.message = problem.message;
+                        changed = true;
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    problem.reset = reset(settings, &problem.key);
+                    changed |= problem.reset;
+                    problems.push(problem);
+                }
+            }
+        }
+        if !changed {
+            return problems;
+        }
+    }
+}
+
+/// Reset the value at `key` to its default, returning whether it could be reset
+fn reset(settings: &mut Settings, key: &str) -> bool {
+    let defaults = Settings::default();
+    match key {
+        "output.frameRate" => settings.output.frame_rate = defaults.output.frame_rate,
+        "output.resolution" => settings.output.resolution = defaults.output.resolution,
+        "output.encoderProfile" => settings.output.encoder_profile = defaults.output.encoder_profile,
+        "output.exportConcurrency" => settings.output.export_concurrency = defaults.output.export_concurrency,
+        "hotkeys.startStop" => settings.hotkeys.start_stop = defaults.hotkeys.start_stop,
+        "hotkeys.pauseResume" => settings.hotkeys.pause_resume = defaults.hotkeys.pause_resume,
+        "hotkeys.cancel" => settings.hotkeys.cancel = defaults.hotkeys.cancel,
+        "hotkeys.toggleCamera" => settings.hotkeys.toggle_camera = defaults.hotkeys.toggle_camera,
+        "recording.sounds.volume" => settings.recording.sounds.volume = defaults.recording.sounds.volume,
+        key => return repair_profile(settings, key),
+    }
+    true
+}
+
+/// Remove the hotkey at `key`, returning whether `key` is a hotkey that was set
+fn clear_hotkey(settings: &mut Settings, key: &str) -> bool {
+    let hotkey = match key {
+        "hotkeys.startStop" => &mut settings.hotkeys.start_stop,
+        "hotkeys.pauseResume" => &mut settings.hotkeys.pause_resume,
+        "hotkeys.cancel" => &mut settings.hotkeys.cancel,
+        "hotkeys.toggleCamera" => &mut settings.hotkeys.toggle_camera,
+        _ => return false,
+    };
+    let was_set = !hotkey.is_empty();
+    hotkey.clear();
+    was_set
+}
+
+/// Reset the profile value at `key`, e.g. `profiles.0.hotkey`, returning
+/// whether it could be reset
+fn repair_profile(settings: &mut Settings, key: &str) -> bool {
+    let mut parts = key.splitn(3, '.');
+    let (Some("profiles"), Some(index), Some(field)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    let Some(profile) = index.parse::<usize>().ok().and_then(|i| settings.profiles.get_mut(i)) else {
+        return false;
+    };
+
+    let defaults = RecordingProfile::default();
+    match field {
+        "hotkey" => profile.hotkey.clear(),
+        "frameRate" => profile.frame_rate = defaults.frame_rate,
+        "resolution" => profile.resolution = defaults.resolution,
+        "encoderProfile" => profile.encoder_profile = defaults.encoder_profile,
+        _ => return false,
+    }
+    true
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use super::layers::ConfigLayers;
+use crate::commands::settings::{commit_settings, get_settings_path, parse_settings, SharedSettings};
+
+/// Editors write in several steps; wait this long for them to settle
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Reload the settings file when it is edited outside the app.
+///
+/// The directory is watched rather than the file, since atomic saves replace
+/// the file and a watch on it would end with the first save.
+pub fn spawn(app_handle: AppHandle) -> Result<()> {
+    let path = get_settings_path();
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."));
+    std::fs::create_dir_all(&dir).context("Failed to create config directory")?;
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to create settings watcher")?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+    std::thread::spawn(move || {
+        // Stops watching when dropped
+        let _watcher = watcher;
+        let touches = |event: &notify::Result<notify::Event>| {
+            matches!(event, Ok(e) if !e.kind.is_access() && e.paths.iter().any(|p| p == &path))
+        };
+
+        while let Ok(event) = rx.recv() {
+            if !touches(&event) {
+                continue;
+            }
+            // Collapse the burst of events a single save produces
+            while rx.recv_timeout(SETTLE_TIME).is_ok() {}
+            tauri::async_runtime::block_on(reload(&app_handle, &path));
+        }
+    });
+
+    Ok(())
+}
+
+/// Apply the file at `path` if it is valid and differs from what is loaded
+async fn reload(app_handle: &AppHandle, path: &Path) {
+    // Gone or half written, the next event brings it back
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return;
+    };
+
+    let (new_settings, problems) = parse_settings(&content, &app_handle.state::<ConfigLayers>());
+    // Values that would be reset mean the edit isn't usable as it is
+    if let Some(problem) = problems.iter().find(|p| p.reset || p.key.is_empty()) {
+        tracing::warn!("Ignoring edited settings file: {}", problem.message);
+        return;
+    }
+
+    let shared = app_handle.state::<SharedSettings>();
+    let mut settings = shared.lock().await;
+    // Our own saves change nothing and are skipped
+    if let Err(e) = commit_settings(app_handle, &mut settings, new_settings, false) {
+        tracing::warn!("Ignoring edited settings file: {}", e);
+    }
+}
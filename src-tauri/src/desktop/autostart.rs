@@ -0,0 +1,68 @@
+//! Launching FlashScreen when the user logs in
+
+pub use platform::set_enabled;
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use anyhow::{anyhow, Context, Result};
+    use std::path::PathBuf;
+
+    /// Name of the XDG autostart entry
+    const ENTRY_NAME: &str = "flashscreen.desktop";
+
+    /// Command that launches this build, preferring the AppImage over the
+    /// path it is mounted at, which changes on every start
+    fn launch_command() -> Result<PathBuf> {
+        if let Some(appimage) = std::env::var_os("APPIMAGE") {
+            return Ok(PathBuf::from(appimage));
+        }
+        std::env::current_exe().context("Failed to locate the executable")
+    }
+
+    fn entry_path() -> Result<PathBuf> {
+        let config = dirs::config_dir().ok_or_else(|| anyhow!("Could not find the config directory"))?;
+        Ok(config.join("autostart").join(ENTRY_NAME))
+    }
+
+    /// Create or remove the XDG autostart entry
+    pub fn set_enabled(enabled: bool) -> Result<()> {
+        let path = entry_path()?;
+
+        if !enabled {
+            if path.exists() {
+                std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+            return Ok(());
+        }
+
+        // Quoted per the Desktop Entry spec so paths with spaces survive
+        let exec = launch_command()?.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\"");
+        let entry = format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=FlashScreen\n\
+             Comment=Screen recorder\n\
+             Exec=\"{}\"\n\
+             Terminal=false\n\
+             X-GNOME-Autostart-enabled=true\n",
+            exec
+        );
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, entry).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use anyhow::{anyhow, Result};
+
+    pub fn set_enabled(enabled: bool) -> Result<()> {
+        if enabled {
+            return Err(anyhow!("Launch at startup is not supported on this platform yet"));
+        }
+        Ok(())
+    }
+}
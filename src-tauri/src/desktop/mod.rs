@@ -0,0 +1,4 @@
+pub mod autostart;
+pub mod notify;
+pub mod sound;
+pub mod tray;
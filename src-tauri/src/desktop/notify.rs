@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::files;
+use crate::commands::settings::SharedSettings;
+use crate::media::probe;
+
+/// Which desktop notifications are shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationPreference {
+    #[default]
+    All,
+    ErrorsOnly,
+    Off,
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour on
+fn format_duration(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+async fn preference(app_handle: &AppHandle) -> NotificationPreference {
+    app_handle.state::<SharedSettings>().lock().await.general.notifications
+}
+
+/// Announce a finished recording with its name, duration and size, offering
+/// to open, reveal, copy or delete it
+pub async fn recording_saved(app_handle: &AppHandle, path: &Path) {
+    if preference(app_handle).await != NotificationPreference::All {
+        return;
+    }
+
+    let parts = files::recording_parts(path);
+    let size: u64 = parts.iter().filter_map(|p| std::fs::metadata(p).ok()).map(|m| m.len()).sum();
+    let mut duration = Some(0.0);
+    for part in &parts {
+        duration = match (duration, probe::probe(app_handle, part).await) {
+            (Some(total), Ok(info)) => Some(total + info.duration),
+            _ => None,
+        };
+    }
+
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut details = Vec::new();
+    if let Some(duration) = duration {
+        details.push(format_duration(duration));
+    }
+    details.push(format_size(size));
+    if parts.len() > 1 {
+        details.push(format!("{} parts", parts.len()));
+    }
+
+    platform::show_saved(app_handle, "Recording saved", &format!("{}\n{}", name, details.join(" · ")), path);
+}
+
+/// Report a failure, unless notifications are switched off
+pub async fn error(app_handle: &AppHandle, title: &str, message: &str) {
+    if preference(app_handle).await == NotificationPreference::Off {
+        return;
+    }
+    show(app_handle, title, message);
+}
+
+/// Plain notification through the notification plugin
+fn show(app_handle: &AppHandle, title: &str, body: &str) {
+    use tauri_plugin_notification::NotificationExt;
+
+    if let Err(e) = app_handle.notification().builder().title(title).body(body).show() {
+        tracing::warn!("Failed to show notification: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use notify_rust::{Notification, Timeout};
+    use std::path::{Path, PathBuf};
+    use tauri::{AppHandle, Emitter, Manager};
+
+    use crate::commands::files::{self, SharedRecentlyDeleted};
+    use crate::error::AppError;
+
+    /// Run a notification action on a saved recording
+    async fn run_action(app_handle: AppHandle, action: &str, path: PathBuf) {
+        let path_string = path.to_string_lossy().to_string();
+        let result = match action {
+            "open" => files::open_file(path_string).await,
+            "folder" => files::show_in_folder(path_string).await,
+            "copy" => {
+                use tauri_plugin_clipboard_manager::ClipboardExt;
+                app_handle
+                    .clipboard()
+                    .write_text(path_string)
+                    .map_err(|e| AppError::Other(format!("Failed to copy path: {}", e)))
+            }
+            "delete" => {
+                let session = files::session_files(&path);
+                match files::move_to_trash(&session) {
+                    Ok(()) => {
+                        let paths: Vec<String> = session.iter().map(|p| p.to_string_lossy().to_string()).collect();
+                        // Undoable from the library like any other delete
+                        app_handle.state::<SharedRecentlyDeleted>().lock().await.push(session);
+                        let _ = app_handle.emit("recordings-changed", paths);
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            super::show(&app_handle, "FlashScreen", &e.to_string());
+        }
+    }
+
+    /// Notification with action buttons; the plugin can't show those on
+    /// desktop, so it talks to the notification daemon directly
+    pub fn show_saved(app_handle: &AppHandle, title: &str, body: &str, path: &Path) {
+        let handle = Notification::new()
+            .appname("FlashScreen")
+            .summary(title)
+            .body(body)
+            .icon("video-x-generic")
+            .action("open", "Open")
+            .action("folder", "Show in folder")
+            .action("copy", "Copy path")
+            .action("delete", "Delete")
+            .timeout(Timeout::Default)
+            .show();
+
+        let handle = match handle {
+            Ok(handle) => handle,
+            Err(e) => {
+                tracing::warn!("Failed to show notification: {}", e);
+                return super::show(app_handle, title, body);
+            }
+        };
+
+        // Blocks until an action is picked or the notification closes
+        let (app_handle, path) = (app_handle.clone(), path.to_path_buf());
+        std::thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                let action = action.to_string();
+                tauri::async_runtime::block_on(run_action(app_handle, &action, path));
+            });
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use std::path::Path;
+    use tauri::AppHandle;
+
+    /// Notifications on this platform can't carry actions yet
+    pub fn show_saved(app_handle: &AppHandle, title: &str, body: &str, _path: &Path) {
+        super::show(app_handle, title, body);
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use rodio::source::{SineWave, Zero};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+
+use crate::commands::settings::SharedSettings;
+
+/// Longest a recording waits for its cue, so a long custom sound can't hold it up
+const MAX_WAIT: Duration = Duration::from_secs(3);
+
+/// Sample rate of the bundled tones
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Moment of a recording a cue is played for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Cue {
+    Start,
+    Stop,
+    /// Pausing and resuming
+    Pause,
+    Error,
+}
+
+impl Cue {
+    /// Bundled tone sequence as (frequency in Hz, milliseconds), 0 Hz is a gap
+    fn tones(&self) -> &'static [(f32, u64)] {
+        match self {
+            Cue::Start => &[(660.0, 90), (0.0, 30), (880.0, 140)],
+            Cue::Stop => &[(880.0, 90), (0.0, 30), (660.0, 140)],
+            Cue::Pause => &[(740.0, 110)],
+            Cue::Error => &[(330.0, 160), (0.0, 60), (330.0, 160)],
+        }
+    }
+}
+
+/// Cues besides start and end, their volume and custom sound files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SoundCues {
+    pub play_pause_sound: bool,
+    pub play_error_sound: bool,
+    /// 0 to 1
+    pub volume: f32,
+    /// Audio files replacing the bundled tones, WAV, FLAC, Ogg Vorbis or MP3
+    pub start_file: Option<String>,
+    pub stop_file: Option<String>,
+    pub pause_file: Option<String>,
+    pub error_file: Option<String>,
+}
+
+impl Default for SoundCues {
+    fn default() -> Self {
+        Self {
+            play_pause_sound: true,
+            play_error_sound: true,
+            volume: 0.6,
+            start_file: None,
+            stop_file: None,
+            pause_file: None,
+            error_file: None,
+        }
+    }
+}
+
+impl SoundCues {
+    /// Custom file configured for `cue`
+    pub fn file(&self, cue: Cue) -> Option<PathBuf> {
+        let file = match cue {
+            Cue::Start => &self.start_file,
+            Cue::Stop => &self.stop_file,
+            Cue::Pause => &self.pause_file,
+            Cue::Error => &self.error_file,
+        };
+        file.as_deref().filter(|f| !f.is_empty()).map(PathBuf::from)
+    }
+}
+
+/// What to play, resolved from the settings
+#[derive(Debug, Clone)]
+pub struct CueSound {
+    pub cue: Cue,
+    pub file: Option<PathBuf>,
+    pub volume: f32,
+}
+
+struct Request {
+    sound: CueSound,
+    /// Signalled once the cue finished playing
+    done: Option<oneshot::Sender<()>>,
+}
+
+/// Plays cues on a dedicated thread, which owns the audio output
+pub struct SoundPlayer {
+    tx: mpsc::Sender<Request>,
+}
+
+impl SoundPlayer {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel::<Request>();
+
+        std::thread::spawn(move || {
+            // Opened on the first cue, so silent setups never touch the device
+            let mut output: Option<(OutputStream, OutputStreamHandle)> = None;
+
+            for request in rx {
+                if output.is_none() {
+                    match OutputStream::try_default() {
+                        Ok(stream) => output = Some(stream),
+                        Err(e) => {
+                            tracing::warn!("Failed to open audio output for sound cues: {}", e);
+                            continue;
+                        }
+                    }
+                }
+                let Some((_, handle)) = &output else {
+                    continue;
+                };
+
+                if let Err(e) = play_on(handle, &request.sound, request.done.is_some()) {
+                    tracing::warn!("Failed to play {:?} sound: {}", request.sound.cue, e);
+                }
+                if let Some(done) = request.done {
+                    let _ = done.send(());
+                }
+            }
+        });
+
+        Self { tx }
+    }
+
+    /// Play without waiting for the cue to end
+    pub fn play(&self, sound: CueSound) {
+        let _ = self.tx.send(Request { sound, done: None });
+    }
+
+    /// Play and wait until the cue ended, at most `MAX_WAIT`
+    pub async fn play_and_wait(&self, sound: CueSound) {
+        let (done, finished) = oneshot::channel();
+        if self.tx.send(Request { sound, done: Some(done) }).is_ok() {
+            let _ = tokio::time::timeout(MAX_WAIT, finished).await;
+        }
+    }
+}
+
+impl Default for SoundPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Queue the custom file, falling back to the bundled tones when it can't be read
+fn play_on(handle: &OutputStreamHandle, sound: &CueSound, wait: bool) -> Result<()> {
+    let sink = Sink::try_new(handle)?;
+    sink.set_volume(sound.volume.clamp(0.0, 1.0));
+
+    let custom = sound.file.as_ref().map(|path| {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Decoder::new(BufReader::new(file)).with_context(|| format!("Failed to decode {}", path.display()))
+    });
+    match custom {
+        Some(Ok(decoder)) => sink.append(decoder.take_duration(MAX_WAIT)),
+        other => {
+            if let Some(Err(e)) = other {
+                tracing::warn!("Using the bundled {:?} sound: {}", sound.cue, e);
+            }
+            for &(freq, ms) in sound.cue.tones() {
+                let duration = Duration::from_millis(ms);
+                if freq > 0.0 {
+                    let fade = Duration::from_millis(10);
+                    sink.append(SineWave::new(freq).take_duration(duration).fade_in(fade).amplify(0.5));
+                } else {
+                    sink.append(Zero::<f32>::new(1, SAMPLE_RATE).take_duration(duration));
+                }
+            }
+        }
+    }
+
+    if wait {
+        sink.sleep_until_end();
+    } else {
+        sink.detach();
+    }
+    Ok(())
+}
+
+/// Resolve `cue` from the current settings, `None` when it is switched off
+async fn cue_sound(app_handle: &AppHandle, cue: Cue) -> Option<CueSound> {
+    let settings = app_handle.state::<SharedSettings>();
+    let recording = &settings.lock().await.recording;
+    let sounds = &recording.sounds;
+
+    let enabled = match cue {
+        Cue::Start => recording.play_start_sound,
+        Cue::Stop => recording.play_end_sound,
+        Cue::Pause => sounds.play_pause_sound,
+        Cue::Error => sounds.play_error_sound,
+    };
+    enabled.then(|| CueSound {
+        cue,
+        file: sounds.file(cue),
+        volume: sounds.volume,
+    })
+}
+
+/// Play `cue` if it is enabled in the settings
+pub async fn play_cue(app_handle: &AppHandle, cue: Cue) {
+    if let Some(sound) = cue_sound(app_handle, cue).await {
+        app_handle.state::<SoundPlayer>().play(sound);
+    }
+}
+
+/// Play `cue` if it is enabled and wait for it to end, e.g. before audio
+/// capture starts so the cue isn't recorded
+pub async fn play_cue_and_wait(app_handle: &AppHandle, cue: Cue) {
+    if let Some(sound) = cue_sound(app_handle, cue).await {
+        app_handle.state::<SoundPlayer>().play_and_wait(sound).await;
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconEvent};
+use tauri::{App, AppHandle, Emitter, Manager, WindowEvent, Wry};
+use tokio::sync::Mutex;
+
+use crate::commands::settings::SharedSettings;
+use crate::recording::{RecordingState, RecordingStatus};
+
+/// Id of the tray icon created from `tauri.conf.json`
+const TRAY_ID: &str = "main";
+
+/// How often the tray checks the recording status
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Menu items whose text follows the recording status
+struct TrayMenu {
+    start_stop: MenuItem<Wry>,
+    pause: MenuItem<Wry>,
+}
+
+/// Show and focus the main window
+fn show_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn on_menu_event(app_handle: &AppHandle, event: MenuEvent) {
+    match event.id.as_ref() {
+        // Same events as the global shortcuts, the window knows the sources
+        "start-stop" => {
+            let _ = app_handle.emit("hotkey-start-stop", ());
+        }
+        "pause" => {
+            let _ = app_handle.emit("hotkey-pause-resume", ());
+        }
+        "show" => show_window(app_handle),
+        "library" => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let directory = app_handle.state::<SharedSettings>().lock().await.output.directory_path();
+                if let Err(e) = std::fs::create_dir_all(&directory) {
+                    tracing::warn!("Failed to create {}: {}", directory.display(), e);
+                }
+                use tauri_plugin_opener::OpenerExt;
+                if let Err(e) = app_handle.opener().open_path(directory.to_string_lossy(), None::<&str>) {
+                    tracing::warn!("Failed to open the library: {}", e);
+                }
+            });
+        }
+        "quit" => app_handle.exit(0),
+        _ => {}
+    }
+}
+
+fn on_tray_icon_event(tray: &TrayIcon, event: TrayIconEvent) {
+    if let TrayIconEvent::Click {
+        button: MouseButton::Left,
+        button_state: MouseButtonState::Up,
+        ..
+    } = event
+    {
+        show_window(tray.app_handle());
+    }
+}
+
+/// App icon with a red dot in the corner, shown while recording
+fn recording_icon(icon: &Image<'_>) -> Image<'static> {
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+
+    let radius = width.min(height) as f64 * 0.22;
+    let (cx, cy) = (width as f64 - radius - 1.0, height as f64 - radius - 1.0);
+    for y in 0..height {
+        for x in 0..width {
+            let distance = ((x as f64 + 0.5 - cx).powi(2) + (y as f64 + 0.5 - cy).powi(2)).sqrt();
+            let i = ((y * width + x) * 4) as usize;
+            if distance <= radius {
+                rgba[i..i + 4].copy_from_slice(&[0xE5, 0x39, 0x35, 0xFF]);
+            } else if distance <= radius + 1.5 {
+                // White ring keeps the dot visible on red icons and dark panels
+                rgba[i..i + 4].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+            }
+        }
+    }
+
+    Image::new(&rgba, width, height).to_owned()
+}
+
+/// Update the menu texts, icon and tooltip for `status`
+fn apply_status(tray: &TrayIcon, menu: &TrayMenu, status: RecordingStatus, icons: &(Image<'static>, Image<'static>)) {
+    let active = matches!(status, RecordingStatus::Recording | RecordingStatus::Paused);
+
+    let _ = menu.start_stop.set_text(if active { "Stop Recording" } else { "Start Recording" });
+    let _ = menu.start_stop.set_enabled(status != RecordingStatus::Encoding);
+    let _ = menu.pause.set_text(if status == RecordingStatus::Paused { "Resume" } else { "Pause" });
+    let _ = menu.pause.set_enabled(active);
+
+    let (tooltip, icon) = match status {
+        RecordingStatus::Recording => ("FlashScreen - Recording", &icons.1),
+        RecordingStatus::Paused => ("FlashScreen - Paused", &icons.1),
+        RecordingStatus::Encoding => ("FlashScreen - Processing", &icons.0),
+        _ => ("FlashScreen", &icons.0),
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+    let _ = tray.set_icon(Some(icon.clone()));
+}
+
+/// Attach the menu to the tray icon and keep it in sync with the recording
+pub fn setup(app: &App) -> tauri::Result<()> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        tracing::warn!("No tray icon configured");
+        return Ok(());
+    };
+
+    let menu = TrayMenu {
+        start_stop: MenuItem::with_id(app, "start-stop", "Start Recording", true, None::<&str>)?,
+        pause: MenuItem::with_id(app, "pause", "Pause", false, None::<&str>)?,
+    };
+    let show = MenuItem::with_id(app, "show", "Show FlashScreen", true, None::<&str>)?;
+    let library = MenuItem::with_id(app, "library", "Open Library", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let separator2 = PredefinedMenuItem::separator(app)?;
+
+    tray.set_menu(Some(Menu::with_items(
+        app,
+        &[&menu.start_stop, &menu.pause, &separator, &show, &library, &separator2, &quit],
+    )?))?;
+    tray.set_show_menu_on_left_click(false)?;
+    tray.on_menu_event(on_menu_event);
+    tray.on_tray_icon_event(on_tray_icon_event);
+
+    let Some(icon) = app.default_window_icon().cloned().map(Image::to_owned) else {
+        return Ok(());
+    };
+    let icons = (icon.clone(), recording_icon(&icon));
+
+    // Status changes come from commands, hotkeys and background tasks alike,
+    // so the tray follows the shared state instead of every call site
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let mut shown = None;
+        loop {
+            let status = app_handle.state::<Arc<Mutex<RecordingState>>>().lock().await.status;
+            if shown != Some(status) {
+                apply_status(&tray, &menu, status, &icons);
+                shown = Some(status);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+
+    Ok(())
+}
+
+/// Hide the window instead of closing it when minimize to tray is on
+pub fn on_window_event(window: &tauri::Window, event: &WindowEvent) {
+    let WindowEvent::CloseRequested { api, .. } = event else {
+        return;
+    };
+    if window.app_handle().tray_by_id(TRAY_ID).is_none() {
+        return;
+    }
+
+    // Runs on the event loop thread, outside the async runtime
+    let minimize_to_tray = window
+        .app_handle()
+        .state::<SharedSettings>()
+        .blocking_lock()
+        .general
+        .minimize_to_tray;
+    if minimize_to_tray {
+        api.prevent_close();
+        let _ = window.hide();
+    }
+}
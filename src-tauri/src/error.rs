@@ -0,0 +1,175 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::config::layers::Layer;
+use crate::config::validate::SettingsProblem;
+
+/// Errors the UI can tell apart and suggest fixes for.
+///
+/// Serialized as `{code, message, details}`; `code` is stable across
+/// releases, `message` is for display and `details` carries the context,
+/// such as the path involved or the tail of FFmpeg's stderr.
+///
+/// The media and recording layers return them inside `anyhow::Error`, and
+/// commands get them back out with `AppError::from_anyhow`.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum AppError {
+    #[error("FFmpeg is not available: {message}")]
+    FfmpegMissing { message: String },
+
+    #[error("FFmpeg failed: {stderr}")]
+    FfmpegFailed { stderr: String },
+
+    #[error("This FFmpeg build lacks {}", missing.join(", "))]
+    FfmpegUnsupported { missing: Vec<String> },
+
+    #[error("A recording is already in progress")]
+    AlreadyRecording,
+
+    #[error("No recording in progress")]
+    NotRecording,
+
+    #[error("Not enough free disk space in {}: {} MB available, {} MB required", path.display(), available_mb, required_mb)]
+    DiskFull {
+        path: PathBuf,
+        available_mb: u64,
+        required_mb: u64,
+    },
+
+    #[error("Permission denied: {}", path.display())]
+    PermissionDenied { path: PathBuf },
+
+    #[error("File not found: {}", path.display())]
+    NotFound { path: PathBuf },
+
+    #[error("A file named {} already exists", path.display())]
+    AlreadyExists { path: PathBuf },
+
+    #[error("Only recordings inside the output directory can be modified")]
+    OutsideLibrary { path: PathBuf },
+
+    #[error("{reason}")]
+    InvalidName { name: String, reason: String },
+
+    #[error("Invalid settings: {}", problems.iter().map(|p| p.message.as_str()).collect::<Vec<_>>().join("; "))]
+    InvalidSettings { problems: Vec<SettingsProblem> },
+
+    #[error("{key} is set by {} and can't be changed", layer.describe())]
+    SettingLocked { key: String, layer: Layer },
+
+    #[error("{message}")]
+    Io { message: String, path: Option<PathBuf> },
+
+    #[error("{0}")]
+    Other(String),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    /// Stable identifier of the kind of error
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::FfmpegMissing { .. } => "FFMPEG_MISSING",
+            AppError::FfmpegFailed { .. } => "FFMPEG_FAILED",
+            AppError::FfmpegUnsupported { .. } => "FFMPEG_UNSUPPORTED",
+            AppError::AlreadyRecording => "ALREADY_RECORDING",
+            AppError::NotRecording => "NOT_RECORDING",
+            AppError::DiskFull { .. } => "DISK_FULL",
+            AppError::PermissionDenied { .. } => "PERMISSION_DENIED",
+            AppError::NotFound { .. } => "NOT_FOUND",
+            AppError::AlreadyExists { .. } => "ALREADY_EXISTS",
+            AppError::OutsideLibrary { .. } => "OUTSIDE_LIBRARY",
+            AppError::InvalidName { .. } => "INVALID_NAME",
+            AppError::InvalidSettings { .. } => "INVALID_SETTINGS",
+            AppError::SettingLocked { .. } => "SETTING_LOCKED",
+            AppError::Io { .. } => "IO_ERROR",
+            AppError::Other(_) => "UNKNOWN",
+        }
+    }
+
+    /// Context for the UI, `null` when there is none
+    pub fn details(&self) -> Value {
+        match self {
+            AppError::FfmpegFailed { stderr } => json!({ "stderr": stderr }),
+            AppError::FfmpegUnsupported { missing } => json!({ "missing": missing }),
+            AppError::DiskFull { path, available_mb, required_mb } => json!({
+                "path": path,
+                "availableMb": available_mb,
+                "requiredMb": required_mb,
+            }),
+            AppError::PermissionDenied { path }
+            | AppError::NotFound { path }
+            | AppError::AlreadyExists { path }
+            | AppError::OutsideLibrary { path } => json!({ "path": path }),
+            AppError::InvalidName { name, .. } => json!({ "name": name }),
+            AppError::InvalidSettings { problems } => json!({ "problems": problems }),
+            AppError::SettingLocked { key, layer } => json!({ "key": key, "layer": layer }),
+            AppError::Io { path: Some(path), .. } => json!({ "path": path }),
+            _ => Value::Null,
+        }
+    }
+
+    /// Classify an I/O error on `path`, describing others as `context: e`
+    pub fn io(context: &str, path: &Path, e: std::io::Error) -> Self {
+        let path = path.to_path_buf();
+        match e.kind() {
+            ErrorKind::PermissionDenied => AppError::PermissionDenied { path },
+            ErrorKind::NotFound => AppError::NotFound { path },
+            ErrorKind::AlreadyExists => AppError::AlreadyExists { path },
+            _ => AppError::Io {
+                message: format!("{}: {}", context, e),
+                path: Some(path),
+            },
+        }
+    }
+
+    /// The `AppError` carried by `e`, or `e` described as `context: e`
+    pub fn from_anyhow(context: &str, e: anyhow::Error) -> Self {
+        let e = match e.downcast::<AppError>() {
+            Ok(error) => return error,
+            Err(e) => e,
+        };
+        let message = format!("{}: {:#}", context, e);
+        if e.downcast_ref::<std::io::Error>().is_some() {
+            AppError::Io { message, path: None }
+        } else {
+            AppError::Other(message)
+        }
+    }
+
+    /// Like `from_anyhow`, classifying I/O errors on `path` like `io`
+    pub fn from_anyhow_at(context: &str, path: &Path, e: anyhow::Error) -> Self {
+        match e.downcast::<std::io::Error>() {
+            Ok(e) => AppError::io(context, path, e),
+            Err(e) => AppError::from_anyhow(context, e),
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("AppError", 3)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("details", &self.details())?;
+        error.end()
+    }
+}
+
+/// Messages of helpers that report plain strings
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+/// Lets commands returning plain messages call the ones returning `AppError`
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
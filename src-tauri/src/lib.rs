@@ -4,20 +4,47 @@ use tokio::sync::Mutex;
 
 // Modules
 mod commands;
+mod config;
+mod desktop;
+mod error;
+mod logging;
+mod media;
 mod recording;
 
 // Re-exports
-use commands::recording::{get_recording_state, start_recording, stop_recording, pause_recording, resume_recording, cancel_recording, check_ffmpeg_available};
-use commands::settings::{get_settings, update_settings, get_output_directory, select_output_directory, load_settings};
-use commands::files::{get_recordings, delete_recording, rename_recording, show_in_folder, open_file};
+use commands::recording::{get_recording_state, start_recording, stop_recording, pause_recording, resume_recording, cancel_recording, check_ffmpeg_available, get_ffmpeg_capabilities};
+use commands::settings::{get_settings, update_settings, get_output_directory, select_output_directory, preview_sound_cue, get_settings_problems, patch_settings, reset_settings, export_settings, import_settings, load_settings};
+use commands::batch::{delete_recordings, move_recordings, export_recordings, zip_recordings};
+use commands::editing::{trim_recording, render_cursor_effects, get_zoom_keyframes, save_zoom_overrides, render_auto_zoom};
+use commands::export::{list_encoder_profiles, export_recording, get_export_jobs, cancel_export_job, retry_export_job, estimate_animation, export_animation, get_audio_tracks, extract_audio};
+use commands::profiles::{get_recording_profiles, save_recording_profile, delete_recording_profile, export_recording_profiles, import_recording_profiles, start_recording_with_profile};
+use commands::diagnostics::export_diagnostics;
+use commands::files::{get_recordings, delete_recording, undo_delete_recording, rename_recording, show_in_folder, open_file, RecentlyDeleted};
+use desktop::sound::SoundPlayer;
+use media::jobs::ExportQueue;
 use recording::{FFmpegRecorder, RecordingState};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let logging = logging::init();
+
     // Initialize shared state
     let recorder = Arc::new(Mutex::new(FFmpegRecorder::new()));
     let recording_state = Arc::new(Mutex::new(RecordingState::new()));
-    let settings = Arc::new(Mutex::new(load_settings()));
+    let layers = config::layers::ConfigLayers::load();
+    let (settings, settings_report) = load_settings(&layers);
+    logging.set_level(settings.general.log_level);
+    tracing::info!("FlashScreen {} starting", env!("CARGO_PKG_VERSION"));
+    // Rewrite the autostart entry in case the executable moved
+    if settings.general.launch_at_startup {
+        if let Err(e) = desktop::autostart::set_enabled(true) {
+            tracing::warn!("Failed to update launch at startup: {}", e);
+        }
+    }
+    let profiles = settings.profiles.clone();
+    let settings = Arc::new(Mutex::new(settings));
+    let recently_deleted = Arc::new(Mutex::new(RecentlyDeleted::new()));
+    let export_queue = Arc::new(Mutex::new(ExportQueue::new()));
 
     tauri::Builder::default()
         // Plugins
@@ -27,16 +54,37 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
         // Managed state
         .manage(recorder)
         .manage(recording_state)
         .manage(settings)
+        .manage(settings_report)
+        .manage(layers)
+        .manage(logging)
+        .manage(media::capabilities::SharedCapabilities::default())
+        .manage(recently_deleted)
+        .manage(export_queue)
+        .manage(SoundPlayer::new())
         // Setup
-        .setup(|app| {
+        .setup(move |app| {
             // Setup global shortcuts
             setup_global_shortcuts(app)?;
+            commands::profiles::sync_hotkeys(app.handle(), &[], &profiles);
+            desktop::tray::setup(app)?;
+            // Probe FFmpeg early so the first recording doesn't wait for it
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = media::capabilities::get(&handle, false).await {
+                    tracing::warn!("Failed to probe FFmpeg capabilities: {:#}", e);
+                }
+            });
+            if let Err(e) = config::watch::spawn(app.handle().clone()) {
+                tracing::warn!("Failed to watch settings file: {}", e);
+            }
             Ok(())
         })
+        .on_window_event(desktop::tray::on_window_event)
         // Commands
         .invoke_handler(tauri::generate_handler![
             // Recording commands
@@ -47,17 +95,55 @@ pub fn run() {
             resume_recording,
             cancel_recording,
             check_ffmpeg_available,
+            get_ffmpeg_capabilities,
             // Settings commands
             get_settings,
             update_settings,
+            patch_settings,
+            reset_settings,
+            export_settings,
+            import_settings,
             get_output_directory,
             select_output_directory,
+            preview_sound_cue,
+            get_settings_problems,
+            // Recording profile commands
+            get_recording_profiles,
+            save_recording_profile,
+            delete_recording_profile,
+            export_recording_profiles,
+            import_recording_profiles,
+            start_recording_with_profile,
             // Files commands
             get_recordings,
             delete_recording,
+            undo_delete_recording,
             rename_recording,
             show_in_folder,
             open_file,
+            // Batch commands
+            delete_recordings,
+            move_recordings,
+            export_recordings,
+            zip_recordings,
+            // Editing commands
+            trim_recording,
+            render_cursor_effects,
+            get_zoom_keyframes,
+            save_zoom_overrides,
+            render_auto_zoom,
+            // Export commands
+            list_encoder_profiles,
+            export_recording,
+            get_export_jobs,
+            cancel_export_job,
+            retry_export_job,
+            estimate_animation,
+            export_animation,
+            get_audio_tracks,
+            extract_audio,
+            // Diagnostics commands
+            export_diagnostics,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -76,7 +162,7 @@ fn setup_global_shortcuts(app: &tauri::App) -> Result<(), Box<dyn std::error::Er
                         let _ = handle.emit($event, ());
                     }
                 }) {
-                    eprintln!("Warning: Failed to register {}: {}", $key, e);
+                    tracing::warn!("Failed to register {}: {}", $key, e);
                 }
             }
         }};
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+/// Daily log files kept before the oldest is deleted
+const LOG_FILES: usize = 7;
+/// FFmpeg session logs kept before the oldest is deleted
+const SESSION_LOGS: usize = 20;
+/// Prefix of the daily log files, e.g. `flashscreen.2024-01-01.log`
+const LOG_PREFIX: &str = "flashscreen";
+
+/// How much the app writes to its log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Our events at `level`, everything else from warnings on
+fn targets(level: LogLevel) -> Targets {
+    Targets::new()
+        .with_target(env!("CARGO_CRATE_NAME"), level.filter())
+        .with_default(LevelFilter::WARN)
+}
+
+/// Directory of the log files, e.g. `~/.local/share/FlashScreen/logs`
+pub fn log_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .map(|p| p.join("FlashScreen").join("logs"))
+        .unwrap_or_else(|| PathBuf::from("logs"))
+}
+
+/// Directory of the per-session FFmpeg logs
+fn session_dir() -> PathBuf {
+    log_dir().join("sessions")
+}
+
+/// The installed subscriber, kept to change its level
+pub struct Logging {
+    filter: reload::Handle<Targets, Registry>,
+    /// Flushes the file writer when dropped
+    _guard: Option<WorkerGuard>,
+}
+
+impl Logging {
+    /// Change the level of the app's own events
+    pub fn set_level(&self, level: LogLevel) {
+        if let Err(e) = self.filter.reload(targets(level)) {
+            tracing::warn!("Failed to change log level: {}", e);
+        }
+    }
+}
+
+/// Log to stderr and to daily files in `log_dir`, at `LogLevel::Info` until
+/// the settings are read
+pub fn init() -> Logging {
+    let (filter, handle) = reload::Layer::new(targets(LogLevel::default()));
+
+    let appender = std::fs::create_dir_all(log_dir()).map_err(anyhow::Error::from).and_then(|_| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_PREFIX)
+            .filename_suffix("log")
+            .max_log_files(LOG_FILES)
+            .build(log_dir())
+            .map_err(anyhow::Error::from)
+    });
+    let (file_layer, guard, error) = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(fmt::layer().with_ansi(false).with_writer(writer)), Some(guard), None)
+        }
+        Err(e) => (None, None, Some(e)),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .init();
+
+    if let Some(e) = error {
+        tracing::warn!("Logging to stderr only, failed to open {}: {:#}", log_dir().display(), e);
+    }
+    Logging { filter: handle, _guard: guard }
+}
+
+/// Log files, the daily ones and the FFmpeg session logs
+pub fn log_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in [log_dir(), session_dir()] {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        files.extend(entries.flatten().map(|e| e.path()).filter(|p| p.is_file()));
+    }
+    files.sort();
+    files
+}
+
+/// FFmpeg command lines and output of one recording, in
+/// `logs/sessions/<recording>.log`
+#[derive(Debug, Clone)]
+pub struct SessionLog {
+    path: PathBuf,
+}
+
+impl SessionLog {
+    /// Log for the recording named `stem`, deleting the oldest session logs
+    pub fn new(stem: &str) -> Self {
+        let dir = session_dir();
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("Failed to create {}: {}", dir.display(), e);
+        }
+        prune(&dir, SESSION_LOGS.saturating_sub(1));
+        Self {
+            path: dir.join(format!("{}.log", stem)),
+        }
+    }
+
+    /// Record the FFmpeg command line of a part
+    pub fn command(&self, part: u32, args: &[String]) {
+        let line = command_line(args);
+        tracing::info!("Recording part {}: {}", part, line);
+        self.write(&format!("[{}] part {}: {}\n", timestamp(), part, line));
+    }
+
+    /// Append output FFmpeg wrote to stderr
+    pub fn stderr(&self, text: &str) {
+        self.write(text);
+        if !text.ends_with('\n') {
+            self.write("\n");
+        }
+    }
+
+    /// Note how the process ended
+    pub fn exited(&self, code: Option<i32>) {
+        let code = code.map_or_else(|| String::from("none"), |c| c.to_string());
+        self.write(&format!("[{}] exited with code {}\n", timestamp(), code));
+    }
+
+    fn write(&self, text: &str) {
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(text.as_bytes()));
+        if let Err(e) = result {
+            tracing::debug!("Failed to write {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+fn timestamp() -> String {
+    chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+}
+
+/// `args` as an FFmpeg command line to paste into a shell
+pub fn command_line(args: &[String]) -> String {
+    std::iter::once("ffmpeg")
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quote `arg` for a shell when it has spaces or quotes
+fn quote(arg: &str) -> String {
+    if arg.is_empty() || arg.contains([' ', '"', '\'']) {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Delete the oldest files in `dir` until `keep` are left
+fn prune(dir: &Path, keep: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    if files.len() <= keep {
+        return;
+    }
+    files.sort();
+    for (_, path) in &files[..files.len() - keep] {
+        let _ = std::fs::remove_file(path);
+    }
+}
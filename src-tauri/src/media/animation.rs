@@ -0,0 +1,209 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::AppHandle;
+
+use super::probe::{self, MediaInfo};
+use super::run_ffmpeg;
+use super::trim::TimeRange;
+
+/// Longest clip that can be exported as an animation, in seconds
+const MAX_DURATION: f64 = 60.0;
+
+/// Animated image format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+}
+
+impl AnimationFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Apng => "png",
+        }
+    }
+
+    /// Rough compressed bytes per pixel of a 256 color frame, used for estimates
+    fn bytes_per_pixel(&self) -> f64 {
+        match self {
+            AnimationFormat::Gif => 0.12,
+            AnimationFormat::Apng => 0.2,
+        }
+    }
+}
+
+/// Dithering applied when mapping frames to the palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Dither {
+    None,
+    Bayer,
+    FloydSteinberg,
+    Sierra,
+}
+
+impl Dither {
+    /// Value of the `paletteuse` `dither` option
+    fn filter_value(&self) -> &'static str {
+        match self {
+            Dither::None => "none",
+            // Smaller scale means a stronger pattern, 5 keeps it subtle
+            Dither::Bayer => "bayer:bayer_scale=5",
+            Dither::FloydSteinberg => "floyd_steinberg",
+            Dither::Sierra => "sierra2_4a",
+        }
+    }
+}
+
+/// What to export as an animation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationOptions {
+    pub format: AnimationFormat,
+    pub range: TimeRange,
+    pub fps: u32,
+    /// Output width in pixels, the height keeps the aspect ratio
+    pub width: u32,
+    pub dither: Dither,
+    /// Drop frames that barely differ from the previous one
+    pub dedupe: bool,
+}
+
+impl AnimationOptions {
+    /// Check the options against the source recording
+    fn validate(&self, info: &MediaInfo) -> Result<TimeRange> {
+        if !(1..=50).contains(&self.fps) {
+            return Err(anyhow!("Frame rate must be between 1 and 50 fps"));
+        }
+        if !(16..=3840).contains(&self.width) {
+            return Err(anyhow!("Width must be between 16 and 3840 pixels"));
+        }
+
+        let range = TimeRange {
+            start: self.range.start,
+            end: self.range.end.min(info.duration),
+        };
+        if !range.start.is_finite() || range.start < 0.0 || range.start >= range.end {
+            return Err(anyhow!(
+                "Invalid time range {:.3}-{:.3} for a {:.3}s recording",
+                range.start,
+                range.end,
+                info.duration
+            ));
+        }
+        if range.end - range.start > MAX_DURATION {
+            return Err(anyhow!("Animations can be at most {} seconds long", MAX_DURATION));
+        }
+        Ok(range)
+    }
+
+    /// Output height for a source of `info`'s size, rounded to even
+    fn height(&self, info: &MediaInfo) -> u32 {
+        if info.width == 0 {
+            return self.width * 9 / 16;
+        }
+        let height = self.width as f64 * info.height as f64 / info.width as f64;
+        ((height / 2.0).round() as u32 * 2).max(2)
+    }
+
+    /// Filter graph scaling, optionally deduplicating, and palettizing the clip
+    fn filter(&self) -> String {
+        let mut chain = vec![
+            format!("fps={}", self.fps),
+            format!("scale={}:-2:flags=lanczos", self.width),
+        ];
+        if self.dedupe {
+            chain.push(String::from("mpdecimate"));
+        }
+
+        // Palette from changed pixels only suits screen content with static areas
+        format!(
+            "[0:v]{},split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither={}:diff_mode=rectangle",
+            chain.join(","),
+            self.dither.filter_value()
+        )
+    }
+}
+
+/// Size estimate and result of an animation export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationResult {
+    pub path: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub frames: u64,
+    pub estimated_size: u64,
+    /// Size of the written file, `None` for estimates
+    pub actual_size: Option<u64>,
+}
+
+/// Estimate the output of `options` for the recording described by `info`
+fn estimate_for(options: &AnimationOptions, info: &MediaInfo) -> Result<AnimationResult> {
+    let range = options.validate(info)?;
+    let height = options.height(info);
+    let frames = ((range.end - range.start) * options.fps as f64).ceil() as u64;
+
+    // Screen recordings are mostly static, so deduplication removes a lot
+    let kept = if options.dedupe { 0.5 } else { 1.0 };
+    let pixels = options.width as f64 * height as f64;
+    let estimated = pixels * options.format.bytes_per_pixel() * frames as f64 * kept;
+
+    Ok(AnimationResult {
+        path: None,
+        width: options.width,
+        height,
+        frames,
+        estimated_size: estimated as u64,
+        actual_size: None,
+    })
+}
+
+/// Estimate the size of an animation of `input` without encoding it
+pub async fn estimate(app_handle: &AppHandle, input: &Path, options: &AnimationOptions) -> Result<AnimationResult> {
+    let info = probe::probe(app_handle, input).await?;
+    estimate_for(options, &info)
+}
+
+/// Encode a range of `input` as an animated GIF or APNG at `output`
+pub async fn export(
+    app_handle: &AppHandle,
+    input: &Path,
+    output: &Path,
+    options: &AnimationOptions,
+) -> Result<AnimationResult> {
+    let info = probe::probe(app_handle, input).await?;
+    let mut result = estimate_for(options, &info)?;
+    let range = options.validate(&info)?;
+
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-ss".into(),
+        format!("{:.6}", range.start),
+        "-t".into(),
+        format!("{:.6}", range.end - range.start),
+        "-i".into(),
+        input.to_string_lossy().to_string(),
+        "-filter_complex".into(),
+        options.filter(),
+        "-an".into(),
+    ];
+    if options.dedupe {
+        // Keep the timing of the frames that remain after mpdecimate
+        args.extend(["-fps_mode".into(), "vfr".into()]);
+    }
+    match options.format {
+        AnimationFormat::Gif => args.extend(["-loop".into(), "0".into(), "-f".into(), "gif".into()]),
+        AnimationFormat::Apng => args.extend(["-plays".into(), "0".into(), "-f".into(), "apng".into()]),
+    }
+    args.extend(["-y".into(), output.to_string_lossy().to_string()]);
+
+    run_ffmpeg(app_handle, &args).await?;
+
+    result.path = Some(output.to_string_lossy().to_string());
+    result.actual_size = Some(std::fs::metadata(output)?.len());
+    Ok(result)
+}
@@ -0,0 +1,361 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::capabilities;
+use super::probe::{self, AudioTrack};
+use super::profile::EncoderProfile;
+use super::{derived_path, escape_filter_path, run_ffmpeg};
+use crate::recording::filename::sanitize_component;
+
+/// Track names written by the recorder, stored as the stream `handler_name`
+pub const MIX_TRACK: &str = "Mixed";
+pub const SYSTEM_TRACK: &str = "System audio";
+pub const MIC_TRACK: &str = "Microphone";
+
+/// Sample rate of extracted audio, also the only rate Opus supports
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Audio file format for extraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Flac,
+    Mp3,
+    Opus,
+}
+
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+        }
+    }
+
+    /// Preferred encoder, replaced by a fallback where the FFmpeg build lacks it
+    fn codec(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "pcm_s16le",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Mp3 => "libmp3lame",
+            AudioFormat::Opus => "libopus",
+        }
+    }
+
+    /// Encoder and muxer arguments for encoding with `encoder`
+    fn codec_args(&self, encoder: &str) -> Vec<String> {
+        let args: &[&str] = match (self, encoder) {
+            (AudioFormat::Wav, _) => &["-f", "wav"],
+            (AudioFormat::Flac, _) => &["-f", "flac"],
+            (AudioFormat::Mp3, "libmp3lame") => &["-q:a", "2", "-f", "mp3"],
+            (AudioFormat::Mp3, _) => &["-b:a", "192k", "-f", "mp3"],
+            // FFmpeg's own Opus encoder is still marked experimental
+            (AudioFormat::Opus, "opus") => &["-b:a", "96k", "-strict", "experimental", "-f", "ogg"],
+            (AudioFormat::Opus, _) => &["-b:a", "96k", "-f", "ogg"],
+        };
+        let mut codec_args = vec![String::from("-c:a"), encoder.to_string()];
+        codec_args.extend(args.iter().map(|s| s.to_string()));
+        codec_args
+    }
+}
+
+/// What to extract from a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractAudioOptions {
+    pub format: AudioFormat,
+    /// Audio track indexes to extract, the first track when empty
+    #[serde(default)]
+    pub tracks: Vec<usize>,
+    /// Write every track to its own file instead of mixing them
+    #[serde(default)]
+    pub separate: bool,
+    /// Normalize to this integrated loudness in LUFS, e.g. -16
+    #[serde(default)]
+    pub loudness: Option<f64>,
+}
+
+/// When the processing chain is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessingMode {
+    /// While capturing, at the cost of some CPU
+    Live,
+    /// After the recording stops, re-encoding only the audio
+    Post,
+}
+
+/// Noise suppression filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoiseSuppression {
+    Off,
+    /// FFT denoiser (`afftdn`), good for steady noise like fans
+    Fft,
+    /// Neural network denoiser (`arnndn`), needs a model file
+    Rnn,
+}
+
+/// Processing chain for one audio source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioProcessing {
+    pub enabled: bool,
+    pub mode: ProcessingMode,
+    /// High-pass cutoff in Hz, 0 disables it
+    pub highpass_hz: u32,
+    pub noise_suppression: NoiseSuppression,
+    /// Noise reduction of `afftdn` in dB
+    pub noise_reduction_db: f64,
+    /// `.rnnn` model used by `arnndn`
+    pub rnn_model: Option<String>,
+    pub compressor: bool,
+    pub loudnorm: bool,
+    /// Integrated loudness target of `loudnorm` in LUFS
+    pub target_lufs: f64,
+}
+
+impl AudioProcessing {
+    /// Defaults for a microphone: rumble filter, FFT denoising and levelling
+    pub fn microphone() -> Self {
+        Self {
+            enabled: false,
+            mode: ProcessingMode::Live,
+            highpass_hz: 80,
+            noise_suppression: NoiseSuppression::Fft,
+            noise_reduction_db: 12.0,
+            rnn_model: None,
+            compressor: true,
+            loudnorm: true,
+            target_lufs: -16.0,
+        }
+    }
+
+    /// Defaults for system audio, which is usually clean already
+    pub fn system_audio() -> Self {
+        Self {
+            highpass_hz: 0,
+            noise_suppression: NoiseSuppression::Off,
+            compressor: false,
+            ..Self::microphone()
+        }
+    }
+
+    /// Filter chain to apply in `mode`, `None` if nothing is applied then
+    pub fn filter(&self, mode: ProcessingMode) -> Result<Option<String>> {
+        if !self.enabled || self.mode != mode {
+            return Ok(None);
+        }
+
+        let mut filters = Vec::new();
+        if self.highpass_hz > 0 {
+            filters.push(format!("highpass=f={}", self.highpass_hz));
+        }
+        match self.noise_suppression {
+            NoiseSuppression::Off => {}
+            NoiseSuppression::Fft => {
+                filters.push(format!("afftdn=nr={}:nf=-40", self.noise_reduction_db.clamp(0.01, 97.0)));
+            }
+            NoiseSuppression::Rnn => {
+                let model = self
+                    .rnn_model
+                    .as_deref()
+                    .filter(|m| Path::new(m).is_file())
+                    .ok_or_else(|| anyhow!("Noise suppression model file not found"))?;
+                filters.push(format!("arnndn=m={}", escape_filter_path(model)));
+            }
+        }
+        if self.compressor {
+            filters.push(String::from("acompressor=threshold=-18dB:ratio=3:attack=20:release=250:makeup=2"));
+        }
+        if self.loudnorm {
+            filters.push(loudnorm_filter(self.target_lufs));
+        }
+
+        Ok((!filters.is_empty()).then(|| filters.join(",")))
+    }
+}
+
+/// EBU R128 loudness normalization to `target` LUFS
+pub fn loudnorm_filter(target: f64) -> String {
+    format!("loudnorm=I={}:TP=-1.5:LRA=11", target.clamp(-70.0, -5.0))
+}
+
+/// Suffix of the file a track is extracted to, e.g. `microphone`
+fn track_suffix(track: &AudioTrack) -> String {
+    let name = match &track.title {
+        Some(title) => title.to_lowercase().replace(' ', "_"),
+        None => format!("track{}", track.index + 1),
+    };
+    sanitize_component(&name)
+}
+
+/// FFmpeg arguments extracting `tracks` of `input` with `encoder`, mixed into `output`
+fn extract_args(
+    input: &Path,
+    tracks: &[&AudioTrack],
+    options: &ExtractAudioOptions,
+    encoder: &str,
+    output: &Path,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-i".into(),
+        input.to_string_lossy().to_string(),
+    ];
+
+    let mut filters = Vec::new();
+    if let Some(target) = options.loudness {
+        filters.push(loudnorm_filter(target));
+    }
+
+    if let [track] = tracks {
+        args.extend(["-map".into(), format!("0:a:{}", track.index)]);
+        if !filters.is_empty() {
+            args.extend(["-af".into(), filters.join(",")]);
+        }
+    } else {
+        let inputs: String = tracks.iter().map(|t| format!("[0:a:{}]", t.index)).collect();
+        let mut graph = format!("{}amix=inputs={}:normalize=0", inputs, tracks.len());
+        for filter in &filters {
+            graph.push(',');
+            graph.push_str(filter);
+        }
+        graph.push_str("[out]");
+        args.extend(["-filter_complex".into(), graph, "-map".into(), "[out]".into()]);
+    }
+
+    args.extend(["-vn".into(), "-ar".into(), SAMPLE_RATE.to_string()]);
+    args.extend(options.format.codec_args(encoder));
+    args.extend(["-y".into(), output.to_string_lossy().to_string()]);
+    args
+}
+
+/// Extract audio tracks of `input` next to it, returning the written files
+pub async fn extract(app_handle: &AppHandle, input: &Path, options: &ExtractAudioOptions) -> Result<Vec<PathBuf>> {
+    let info = probe::probe(app_handle, input).await?;
+    if info.audio_tracks.is_empty() {
+        return Err(anyhow!("{} has no audio", input.display()));
+    }
+
+    let indexes = if options.tracks.is_empty() { vec![0] } else { options.tracks.clone() };
+    let tracks = indexes
+        .iter()
+        .map(|i| {
+            info.audio_tracks
+                .iter()
+                .find(|t| t.index == *i)
+                .ok_or_else(|| anyhow!("Recording has no audio track {}", i + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // One output per track, or all selected tracks mixed into one
+    let groups: Vec<(Vec<&AudioTrack>, String)> = if options.separate {
+        tracks.iter().map(|t| (vec![*t], track_suffix(t))).collect()
+    } else {
+        let suffix = match tracks.as_slice() {
+            [track] => track_suffix(track),
+            _ => String::from("audio"),
+        };
+        vec![(tracks, suffix)]
+    };
+
+    let encoder = capabilities::encoder(app_handle, options.format.codec()).await?;
+    let mut outputs = Vec::new();
+    for (tracks, suffix) in groups {
+        let output = derived_path(input, &suffix, options.format.extension(), |p| outputs.contains(&p.to_path_buf()));
+        let name = output.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let temp = output.with_file_name(format!("{}.tmp", name));
+
+        let result = run_ffmpeg(app_handle, &extract_args(input, &tracks, options, &encoder, &temp))
+            .await
+            .and_then(|_| std::fs::rename(&temp, &output).map_err(Into::into));
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e);
+        }
+        outputs.push(output);
+    }
+
+    Ok(outputs)
+}
+
+/// Apply processing chains to the source tracks of a finished recording.
+///
+/// Tracks are matched by the names the recorder gives them; the mixed track
+/// is rebuilt from the processed sources. Video is copied as is.
+pub async fn post_process(
+    app_handle: &AppHandle,
+    path: &Path,
+    system_audio: Option<&str>,
+    microphone: Option<&str>,
+    encoder: &EncoderProfile,
+) -> Result<()> {
+    let info = probe::probe(app_handle, path).await?;
+    let title = |t: &AudioTrack| t.title.clone().unwrap_or_default();
+    let has_mix = info.audio_tracks.iter().any(|t| title(t) == MIX_TRACK);
+
+    let mut graph = Vec::new();
+    let mut mix_inputs = String::new();
+    for track in &info.audio_tracks {
+        let chain = match title(track).as_str() {
+            SYSTEM_TRACK => system_audio.unwrap_or("anull"),
+            MIC_TRACK => microphone.unwrap_or("anull"),
+            _ => continue,
+        };
+        let n = track.index;
+        if has_mix {
+            graph.push(format!("[0:a:{n}]{chain},asplit=2[s{n}][m{n}]"));
+            mix_inputs.push_str(&format!("[m{n}]"));
+        } else {
+            graph.push(format!("[0:a:{n}]{chain}[s{n}]"));
+        }
+    }
+    if graph.is_empty() {
+        return Ok(());
+    }
+    if has_mix {
+        graph.push(format!("{}amix=inputs={}:normalize=0[mix]", mix_inputs, mix_inputs.matches('[').count()));
+    }
+
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-i".into(),
+        path.to_string_lossy().to_string(),
+        "-filter_complex".into(),
+        graph.join(";"),
+        "-map".into(),
+        "0:v?".into(),
+    ];
+    for track in &info.audio_tracks {
+        let name = title(track);
+        let map = match name.as_str() {
+            MIX_TRACK => String::from("[mix]"),
+            SYSTEM_TRACK | MIC_TRACK => format!("[s{}]", track.index),
+            _ => format!("0:a:{}", track.index),
+        };
+        args.extend(["-map".into(), map]);
+        if !name.is_empty() {
+            args.extend([format!("-metadata:s:a:{}", track.index), format!("handler_name={}", name)]);
+        }
+    }
+    args.extend(["-c:v".into(), "copy".into()]);
+    args.extend(encoder.audio_codec_args());
+
+    let temp = path.with_extension("audio.tmp");
+    args.extend(["-movflags".into(), "+faststart".into(), "-f".into(), "mp4".into(), "-y".into()]);
+    args.push(temp.to_string_lossy().to_string());
+
+    let result = run_ffmpeg(app_handle, &args)
+        .await
+        .and_then(|_| std::fs::rename(&temp, path).map_err(Into::into));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
@@ -0,0 +1,489 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_shell::ShellExt;
+
+use super::profile::EncoderProfile;
+use crate::error::AppError;
+
+/// Encoders that can stand in for each other, best first. A profile asking
+/// for one that is missing gets the first available one of its list.
+const ENCODER_FALLBACKS: &[&[&str]] = &[
+    &["libx264", "h264_nvenc", "h264_qsv", "h264_amf", "h264_videotoolbox", "h264_mf", "libopenh264"],
+    &["libx265", "hevc_nvenc", "hevc_qsv", "hevc_amf", "hevc_videotoolbox", "hevc_mf"],
+    &["libsvtav1", "libaom-av1", "librav1e", "av1_nvenc", "av1_qsv", "av1_amf"],
+    &["aac", "libfdk_aac", "aac_mf", "aac_at"],
+    &["libmp3lame", "mp3_mf"],
+    &["libopus", "opus"],
+];
+
+/// Kind of stream an encoder produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamKind {
+    Video,
+    Audio,
+    Subtitle,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Encoder {
+    pub name: String,
+    pub kind: StreamKind,
+    pub description: String,
+}
+
+/// What the bundled FFmpeg build supports
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegCapabilities {
+    /// First line of `ffmpeg -version`
+    pub version: String,
+    pub encoders: Vec<Encoder>,
+    pub muxers: BTreeSet<String>,
+    pub filters: BTreeSet<String>,
+    /// Capture devices, e.g. `gdigrab` or `x11grab`
+    pub input_devices: BTreeSet<String>,
+    pub output_devices: BTreeSet<String>,
+    /// Whether a fallback encoder managed to encode a test frame, by name
+    #[serde(skip)]
+    pub tested: BTreeMap<String, bool>,
+}
+
+/// Capabilities probed from the sidecar, `None` until the first probe.
+///
+/// Behind a plain lock, as settings validation reads it outside async code.
+pub type SharedCapabilities = Arc<RwLock<Option<FfmpegCapabilities>>>;
+
+/// Encoder lines of `ffmpeg -encoders`, e.g. `V....D libx264  libx264 H.264`
+fn parse_encoders(text: &str) -> Vec<Encoder> {
+    text.lines()
+        .skip_while(|l| !l.trim_start().starts_with("---"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            let flags = tokens.next()?;
+            let name = tokens.next()?;
+            let kind = match flags.chars().next()? {
+                'V' => StreamKind::Video,
+                'A' => StreamKind::Audio,
+                'S' => StreamKind::Subtitle,
+                _ => return None,
+            };
+            Some(Encoder {
+                name: name.to_string(),
+                kind,
+                description: tokens.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect()
+}
+
+/// Names in the format lists of `ffmpeg -muxers` and `ffmpeg -devices`,
+/// e.g. ` DE alsa  ALSA audio output`, that have `flag` set
+fn parse_formats(text: &str, flag: char) -> BTreeSet<String> {
+    text.lines()
+        .skip_while(|l| !l.trim_start().starts_with("--"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            let flags = tokens.next()?;
+            let names = tokens.next()?;
+            let is_flags = flags.chars().all(|c| matches!(c, 'D' | 'E' | 'd' | '.'));
+            (is_flags && flags.contains(flag)).then_some(names)
+        })
+        .flat_map(|names| names.split(',').map(String::from).collect::<Vec<_>>())
+        .collect()
+}
+
+/// Filter lines of `ffmpeg -filters`, e.g. ` TSC amix  N->A  Audio mixing.`
+fn parse_filters(text: &str) -> BTreeSet<String> {
+    text.lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().take(3).collect();
+            match tokens[..] {
+                [_, name, io] if io.contains("->") => Some(name.to_string()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Names of the filters in a filter chain such as `[a]palettegen[p],fps=12`
+pub fn filter_names(chain: &str) -> Vec<String> {
+    // Split on `,` and `;` outside quoted and escaped option values
+    let mut filters = vec![String::new()];
+    let (mut quoted, mut escaped) = (false, false);
+    for c in chain.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\'' => quoted = !quoted,
+            ',' | ';' if !quoted => {
+                filters.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(filter) = filters.last_mut() {
+            filter.push(c);
+        }
+    }
+
+    filters
+        .iter()
+        .filter_map(|filter| {
+            let mut filter = filter.trim();
+            // Drop the input pad labels, e.g. `[a]`
+            while let Some(rest) = filter.strip_prefix('[') {
+                filter = rest.split_once(']').map_or("", |(_, rest)| rest.trim_start());
+            }
+            let name = filter.split(['=', '[']).next()?.trim();
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+impl FfmpegCapabilities {
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoders.iter().any(|e| e.name == name)
+    }
+
+    pub fn has_muxer(&self, name: &str) -> bool {
+        self.muxers.contains(name)
+    }
+
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.contains(name)
+    }
+
+    pub fn has_input_device(&self, name: &str) -> bool {
+        self.input_devices.contains(name)
+    }
+
+    /// `codec` if available, or the best available encoder standing in for
+    /// it that didn't fail a test encode
+    pub fn best_encoder(&self, codec: &str) -> Option<String> {
+        if self.has_encoder(codec) {
+            return Some(codec.to_string());
+        }
+        let fallbacks = ENCODER_FALLBACKS.iter().find(|list| list.contains(&codec))?;
+        fallbacks
+            .iter()
+            .find(|e| self.has_encoder(e) && self.tested.get(**e) != Some(&false))
+            .map(|e| e.to_string())
+    }
+
+    /// Filters of `chain` this build lacks
+    pub fn missing_filters(&self, chain: &str) -> Vec<String> {
+        filter_names(chain).into_iter().filter(|f| !self.has_filter(f)).collect()
+    }
+
+    /// Encoders, muxer and filters `profile` needs that neither this build nor
+    /// a fallback provide
+    pub fn missing(&self, profile: &EncoderProfile) -> Vec<String> {
+        let mut missing: Vec<String> = [&profile.video_codec, &profile.audio_codec]
+            .into_iter()
+            .flatten()
+            .filter(|codec| self.best_encoder(codec).is_none())
+            .cloned()
+            .collect();
+        if !self.has_muxer(&profile.format) {
+            missing.push(profile.format.clone());
+        }
+        if let Some(filter) = &profile.filter {
+            missing.extend(self.missing_filters(filter));
+        }
+        missing
+    }
+
+    /// `profile` with missing encoders replaced by available ones, its
+    /// speed and quality translated to the options of the replacement
+    fn adapt(&self, profile: &EncoderProfile) -> EncoderProfile {
+        let mut adapted = profile.clone();
+        if let Some(codec) = &profile.video_codec {
+            match self.best_encoder(codec) {
+                Some(best) if &best != codec => {
+                    tracing::info!("{} is not available, encoding {} with {}", codec, profile.id, best);
+                    adapted = adapted.with_video_codec(&best);
+                }
+                _ => {}
+            }
+        }
+        if let Some(codec) = &profile.audio_codec {
+            match self.best_encoder(codec) {
+                Some(best) if &best != codec => {
+                    tracing::info!("{} is not available, encoding {} with {}", codec, profile.id, best);
+                    adapted.audio_codec = Some(best);
+                }
+                _ => {}
+            }
+        }
+        adapted
+    }
+}
+
+/// Standard output of `ffmpeg -hide_banner <flag>`
+async fn list(app_handle: &AppHandle, flag: &str) -> Result<String> {
+    let output = app_handle
+        .shell()
+        .sidecar("ffmpeg")
+        .map_err(|e| AppError::FfmpegMissing { message: e.to_string() })?
+        .args(["-hide_banner", flag])
+        .output()
+        .await
+        .map_err(|e| AppError::FfmpegMissing { message: e.to_string() })?;
+    if !output.status.success() {
+        return Err(anyhow!("ffmpeg {} failed: {}", flag, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Ask the sidecar what it supports
+async fn probe(app_handle: &AppHandle) -> Result<FfmpegCapabilities> {
+    let version = list(app_handle, "-version").await?;
+    let capabilities = FfmpegCapabilities {
+        version: version.lines().next().unwrap_or_default().to_string(),
+        encoders: parse_encoders(&list(app_handle, "-encoders").await?),
+        muxers: parse_formats(&list(app_handle, "-muxers").await?, 'E'),
+        filters: parse_filters(&list(app_handle, "-filters").await?),
+        input_devices: BTreeSet::new(),
+        output_devices: BTreeSet::new(),
+        tested: BTreeMap::new(),
+    };
+    let devices = list(app_handle, "-devices").await?;
+    let capabilities = FfmpegCapabilities {
+        input_devices: parse_formats(&devices, 'D'),
+        output_devices: parse_formats(&devices, 'E'),
+        ..capabilities
+    };
+
+    tracing::info!(
+        "{}: {} encoders, {} muxers, {} filters, input devices {:?}",
+        capabilities.version,
+        capabilities.encoders.len(),
+        capabilities.muxers.len(),
+        capabilities.filters.len(),
+        capabilities.input_devices
+    );
+    Ok(capabilities)
+}
+
+/// Capabilities of the sidecar, probed on first use or when `refresh` is set
+pub async fn get(app_handle: &AppHandle, refresh: bool) -> Result<FfmpegCapabilities> {
+    let shared = app_handle.state::<SharedCapabilities>();
+    if !refresh {
+        if let Some(capabilities) = cached(&shared) {
+            return Ok(capabilities);
+        }
+    }
+
+    let capabilities = probe(app_handle).await?;
+    if let Ok(mut cache) = shared.write() {
+        *cache = Some(capabilities.clone());
+    }
+    Ok(capabilities)
+}
+
+/// Capabilities if they were probed already
+pub fn cached(shared: &SharedCapabilities) -> Option<FfmpegCapabilities> {
+    shared.read().ok().and_then(|c| c.clone())
+}
+
+/// Whether `encoder` can encode one frame. `-encoders` lists everything
+/// compiled in, including hardware encoders without a device or driver.
+async fn test_encode(app_handle: &AppHandle, encoder: &str, kind: StreamKind) -> bool {
+    let (source, frames, codec) = match kind {
+        StreamKind::Video => ("nullsrc", "-frames:v", "-c:v"),
+        StreamKind::Audio => ("anullsrc", "-frames:a", "-c:a"),
+        StreamKind::Subtitle => return true,
+    };
+    let args = [
+        "-hide_banner", "-loglevel", "error", "-f", "lavfi", "-i", source, frames, "1", codec, encoder, "-f", "null",
+        "-",
+    ];
+    let output = match app_handle.shell().sidecar("ffmpeg") {
+        Ok(command) => command.args(args).output().await,
+        Err(_) => return false,
+    };
+    match output {
+        Ok(output) if output.status.success() => true,
+        Ok(output) => {
+            tracing::info!("{} failed a test encode: {}", encoder, String::from_utf8_lossy(&output.stderr).trim());
+            false
+        }
+        Err(e) => {
+            tracing::warn!("Failed to test {}: {}", encoder, e);
+            false
+        }
+    }
+}
+
+/// Test the fallback that would stand in for `codec` until one works,
+/// remembering the outcome in `capabilities` and the shared cache
+async fn test_fallbacks(app_handle: &AppHandle, capabilities: &mut FfmpegCapabilities, codec: &str) {
+    if capabilities.has_encoder(codec) {
+        return;
+    }
+    while let Some(fallback) = capabilities.best_encoder(codec) {
+        if capabilities.tested.contains_key(&fallback) {
+            return;
+        }
+        let kind = capabilities
+            .encoders
+            .iter()
+            .find(|e| e.name == fallback)
+            .map_or(StreamKind::Video, |e| e.kind);
+        let works = test_encode(app_handle, &fallback, kind).await;
+        capabilities.tested.insert(fallback.clone(), works);
+        if let Ok(mut cache) = app_handle.state::<SharedCapabilities>().write() {
+            if let Some(cached) = cache.as_mut() {
+                cached.tested.insert(fallback, works);
+            }
+        }
+        if works {
+            return;
+        }
+    }
+}
+
+/// `profile` adapted to the sidecar, unchanged when it can't be probed.
+///
+/// Fallback encoders are tested before they are chosen, once per probe.
+pub async fn adapt(app_handle: &AppHandle, profile: EncoderProfile) -> EncoderProfile {
+    let mut capabilities = match get(app_handle, false).await {
+        Ok(capabilities) => capabilities,
+        Err(e) => {
+            tracing::warn!("Failed to probe FFmpeg capabilities: {:#}", e);
+            return profile;
+        }
+    };
+    for codec in [&profile.video_codec, &profile.audio_codec].into_iter().flatten() {
+        test_fallbacks(app_handle, &mut capabilities, codec).await;
+    }
+    capabilities.adapt(&profile)
+}
+
+/// Encoder for `codec`: `codec` itself or a tested fallback standing in
+/// for it, or `codec` as is when the sidecar can't be probed.
+///
+/// Fails when the build has no encoder for it.
+pub async fn encoder(app_handle: &AppHandle, codec: &str) -> Result<String> {
+    let mut capabilities = match get(app_handle, false).await {
+        Ok(capabilities) => capabilities,
+        Err(e) => {
+            tracing::warn!("Failed to probe FFmpeg capabilities: {:#}", e);
+            return Ok(codec.to_string());
+        }
+    };
+    test_fallbacks(app_handle, &mut capabilities, codec).await;
+    capabilities
+        .best_encoder(codec)
+        .ok_or_else(|| anyhow!("This FFmpeg build has no {} encoder or one to stand in for it", codec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODERS: &str = "Encoders:
+ V..... = Video
+ A..... = Audio
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+ V....D h264_nvenc           NVIDIA NVENC H.264 encoder (codec h264)
+ A....D aac                  AAC (Advanced Audio Coding)
+ S..... mov_text             3GPP Timed Text subtitle
+";
+
+    #[test]
+    fn parses_encoders() {
+        let encoders = parse_encoders(ENCODERS);
+        let names: Vec<(&str, StreamKind)> = encoders.iter().map(|e| (e.name.as_str(), e.kind)).collect();
+        assert_eq!(
+            names,
+            [
+                ("libx264", StreamKind::Video),
+                ("h264_nvenc", StreamKind::Video),
+                ("aac", StreamKind::Audio),
+                ("mov_text", StreamKind::Subtitle),
+            ]
+        );
+        assert_eq!(encoders[2].description, "AAC (Advanced Audio Coding)");
+    }
+
+    #[test]
+    fn parses_formats_with_a_flag() {
+        let devices = "Devices:
+ D. = Demuxing supported
+ .E = Muxing supported
+ --
+ DE alsa            ALSA audio output
+ D  x11grab         X11 screen capture, using XCB
+  E sdl,sdl2        SDL2 output device
+";
+        let inputs: Vec<String> = parse_formats(devices, 'D').into_iter().collect();
+        let outputs: Vec<String> = parse_formats(devices, 'E').into_iter().collect();
+        assert_eq!(inputs, ["alsa", "x11grab"]);
+        assert_eq!(outputs, ["alsa", "sdl", "sdl2"]);
+    }
+
+    #[test]
+    fn parses_filters() {
+        let filters = "Filters:
+  T.. = Timeline support
+ TSC amix              N->A       Audio mixing.
+ ... palettegen        V->V       Find the optimal palette for a given stream.
+ ... nullsrc           |->V       Null video source, return unprocessed video frames.
+";
+        let names: Vec<String> = parse_filters(filters).into_iter().collect();
+        assert_eq!(names, ["amix", "nullsrc", "palettegen"]);
+    }
+
+    #[test]
+    fn names_filters_of_a_chain() {
+        assert_eq!(
+            filter_names("split[a][b];[a]palettegen[p];[b][p]paletteuse"),
+            ["split", "palettegen", "paletteuse"]
+        );
+        assert_eq!(filter_names("drawtext=text='a, b':x=1,fps=12"), ["drawtext", "fps"]);
+        assert_eq!(filter_names("scale=-2:'min(720,ih)'"), ["scale"]);
+    }
+
+    fn capabilities(encoders: &[&str]) -> FfmpegCapabilities {
+        FfmpegCapabilities {
+            encoders: encoders
+                .iter()
+                .map(|name| Encoder {
+                    name: name.to_string(),
+                    kind: StreamKind::Video,
+                    description: String::new(),
+                })
+                .collect(),
+            ..FfmpegCapabilities::default()
+        }
+    }
+
+    #[test]
+    fn falls_back_to_encoders_that_did_not_fail_a_test() {
+        let mut capabilities = capabilities(&["h264_nvenc", "h264_qsv"]);
+        assert_eq!(capabilities.best_encoder("libx264").as_deref(), Some("h264_nvenc"));
+        capabilities.tested.insert(String::from("h264_nvenc"), false);
+        assert_eq!(capabilities.best_encoder("libx264").as_deref(), Some("h264_qsv"));
+        assert_eq!(capabilities.best_encoder("libx265"), None);
+    }
+
+    #[test]
+    fn adapt_translates_speed_and_quality() {
+        let av1 = EncoderProfile::find("av1").unwrap();
+        let adapted = capabilities(&["libaom-av1"]).adapt(&av1);
+        assert_eq!(adapted.video_codec.as_deref(), Some("libaom-av1"));
+        // Preset 8 of 13 is about as fast as cpu-used 5 of 8
+        assert_eq!(adapted.preset.as_deref(), Some("5"));
+        assert_eq!(adapted.crf, Some(35));
+        assert!(adapted.video_codec_args().windows(2).any(|w| w == ["-cpu-used", "5"]));
+    }
+}
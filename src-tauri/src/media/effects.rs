@@ -0,0 +1,306 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::probe;
+use super::profile::EncoderProfile;
+use super::{ass_time, escape_filter_path, run_ffmpeg};
+use crate::recording::telemetry::{CursorEventKind, Telemetry};
+
+/// Cursor positions per second written to the overlay; libass moves the
+/// cursor linearly between them
+const SAMPLE_RATE: u64 = 25;
+
+/// How long a click ripple grows and fades, in milliseconds
+const RIPPLE_MS: u64 = 500;
+
+/// Arrow pointer as an ASS drawing, tip at the origin
+const ARROW: &str = "m 0 0 l 0 20 5 15 9 23 12 22 8 14 14 14";
+
+/// Cursor effects composited from telemetry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CursorEffects {
+    /// How strongly movement is smoothed, 0 follows the pointer exactly
+    pub smoothing: f64,
+    pub click_ripples: bool,
+    /// Dim everything but a circle around the cursor
+    pub spotlight: bool,
+    /// Cursor size relative to the captured screen
+    pub cursor_scale: f64,
+    /// Ripple color as `#RRGGBB`
+    pub ripple_color: String,
+    /// Spotlight radius in video pixels
+    pub spotlight_radius: u32,
+}
+
+impl Default for CursorEffects {
+    fn default() -> Self {
+        Self {
+            smoothing: 0.6,
+            click_ripples: false,
+            spotlight: false,
+            cursor_scale: 1.0,
+            ripple_color: String::from("#FFC83D"),
+            spotlight_radius: 160,
+        }
+    }
+}
+
+/// `#RRGGBB` to the `&HBBGGRR&` order ASS uses
+fn ass_color(color: &str) -> String {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return String::from("&HFFFFFF&");
+    }
+    format!("&H{}{}{}&", &hex[4..6], &hex[2..4], &hex[0..2]).to_uppercase()
+}
+
+/// Circle of radius `r` around (`cx`, `cy`) as an ASS drawing
+fn circle(cx: f64, cy: f64, r: f64) -> String {
+    // Four cubic curves with the usual 0.5523 control point factor
+    let k = r * 0.5523;
+    format!(
+        "m {x0} {y0} b {x1} {y0} {x2} {y1} {x2} {cy} b {x2} {y2} {x1} {y3} {cx} {y3} b {x3} {y3} {x4} {y2} {x4} {cy} b {x4} {y1} {x3} {y0} {cx} {y0}",
+        x0 = cx.round(),
+        y0 = (cy - r).round(),
+        x1 = (cx + k).round(),
+        x2 = (cx + r).round(),
+        y1 = (cy - k).round(),
+        y2 = (cy + k).round(),
+        y3 = (cy + r).round(),
+        x3 = (cx - k).round(),
+        x4 = (cx - r).round(),
+        cx = cx.round(),
+        cy = cy.round(),
+    )
+}
+
+/// Maps telemetry to the part of the recording being rendered
+struct Timeline<'a> {
+    telemetry: &'a Telemetry,
+    /// Telemetry time of the first frame, in milliseconds
+    offset: u64,
+    duration: u64,
+    scale_x: f64,
+    scale_y: f64,
+}
+
+impl Timeline<'_> {
+    /// Screen position to video position
+    fn to_video(&self, x: i32, y: i32) -> (f64, f64) {
+        let area = &self.telemetry.area;
+        ((x - area.x) as f64 * self.scale_x, (y - area.y) as f64 * self.scale_y)
+    }
+
+    /// Pointer position at fixed intervals, smoothed, in video coordinates
+    fn samples(&self, smoothing: f64) -> Vec<(u64, f64, f64)> {
+        let events = &self.telemetry.cursor;
+        let Some(first) = events.first() else {
+            return Vec::new();
+        };
+
+        let alpha = 1.0 - smoothing.clamp(0.0, 0.95);
+        let step = 1000 / SAMPLE_RATE;
+        let mut next = 0;
+        let mut raw = (first.x, first.y);
+        let mut smoothed: Option<(f64, f64)> = None;
+        let mut samples = Vec::new();
+
+        // Start early so smoothing has settled by the first frame
+        let mut t = self.offset.saturating_sub(step * 10);
+        while t <= self.offset + self.duration {
+            while next < events.len() && events[next].t <= t {
+                raw = (events[next].x, events[next].y);
+                next += 1;
+            }
+
+            let target = self.to_video(raw.0, raw.1);
+            let position = match smoothed {
+                Some((x, y)) => (x + (target.0 - x) * alpha, y + (target.1 - y) * alpha),
+                None => target,
+            };
+            smoothed = Some(position);
+
+            if t >= self.offset {
+                samples.push((t - self.offset, position.0, position.1));
+            }
+            t += step;
+        }
+
+        samples
+    }
+}
+
+/// Build an ASS overlay drawing the cursor effects for one recording part
+pub fn render_overlay(
+    telemetry: &Telemetry,
+    effects: &CursorEffects,
+    width: u32,
+    height: u32,
+    offset: u64,
+    duration: u64,
+) -> String {
+    let area = &telemetry.area;
+    let timeline = Timeline {
+        telemetry,
+        offset,
+        duration,
+        scale_x: width as f64 / area.width.max(1) as f64,
+        scale_y: height as f64 / area.height.max(1) as f64,
+    };
+
+    let mut ass = format!(
+        "[Script Info]\nScriptType: v4.00+\nPlayResX: {width}\nPlayResY: {height}\nScaledBorderAndShadow: yes\n\n\
+         [V4+ Styles]\n\
+         Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n\
+         Style: Default,Arial,20,&H00FFFFFF,&H00FFFFFF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,0,0,7,0,0,0,1\n\n\
+         [Events]\n\
+         Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n"
+    );
+
+    let samples = timeline.samples(effects.smoothing);
+    let cursor_scale = 100.0 * effects.cursor_scale * timeline.scale_x;
+    let radius = effects.spotlight_radius as f64;
+
+    for pair in samples.windows(2) {
+        let ((t0, x0, y0), (t1, x1, y1)) = (pair[0], pair[1]);
+        let (start, end) = (ass_time(t0), ass_time(t1));
+
+        if effects.spotlight {
+            let _ = writeln!(
+                ass,
+                "Dialogue: 0,{start},{end},Default,,0,0,0,,{{\\an7\\pos(0,0)\\p1\\1c&H000000&\\1a&H70&\\iclip(1,{})}}m 0 0 l {width} 0 {width} {height} 0 {height}{{\\p0}}",
+                circle(x0, y0, radius)
+            );
+        }
+
+        let _ = writeln!(
+            ass,
+            "Dialogue: 2,{start},{end},Default,,0,0,0,,{{\\an7\\move({x0:.0},{y0:.0},{x1:.0},{y1:.0})\\p1\\bord1.5\\1c&HFFFFFF&\\3c&H000000&\\fscx{cursor_scale:.0}\\fscy{cursor_scale:.0}}}{ARROW}{{\\p0}}"
+        );
+    }
+
+    if effects.click_ripples {
+        let color = ass_color(&effects.ripple_color);
+        let size = 24.0 * timeline.scale_x.max(0.5);
+
+        for event in &telemetry.cursor {
+            if !matches!(event.kind, CursorEventKind::Down { .. }) || event.t < offset || event.t > offset + duration {
+                continue;
+            }
+            let (x, y) = timeline.to_video(event.x, event.y);
+            let t = event.t - offset;
+            let _ = writeln!(
+                ass,
+                "Dialogue: 1,{},{},Default,,0,0,0,,{{\\an5\\pos({x:.0},{y:.0})\\p1\\1a&HFF&\\bord3\\3c{color}\\fscx30\\fscy30\\t(0,{RIPPLE_MS},\\fscx100\\fscy100\\3a&HFF&)}}{}{{\\p0}}",
+                ass_time(t),
+                ass_time(t + RIPPLE_MS),
+                circle(0.0, 0.0, size)
+            );
+        }
+    }
+
+    ass
+}
+
+/// Composite cursor effects onto `source`, writing `output`.
+///
+/// `offset` is where `source` starts in the telemetry, in milliseconds, so
+/// numbered parts can be rendered one by one. Audio is copied untouched.
+/// Returns the duration of `source` in milliseconds.
+pub async fn render(
+    app_handle: &AppHandle,
+    source: &Path,
+    output: &Path,
+    telemetry: &Telemetry,
+    effects: &CursorEffects,
+    offset: u64,
+    encoder: &EncoderProfile,
+) -> Result<u64> {
+    let info = probe::probe(app_handle, source).await?;
+    if info.width == 0 || info.height == 0 {
+        return Err(anyhow!("Could not read the video size of {}", source.display()));
+    }
+
+    let overlay = render_overlay(
+        telemetry,
+        effects,
+        info.width,
+        info.height,
+        offset,
+        (info.duration * 1000.0) as u64,
+    );
+    let overlay_path = output.with_extension("effects.ass");
+    std::fs::write(&overlay_path, overlay).context("Failed to write cursor overlay")?;
+
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-i".into(),
+        source.to_string_lossy().to_string(),
+        "-map".into(),
+        "0".into(),
+        "-vf".into(),
+        format!("ass={}", escape_filter_path(&overlay_path.to_string_lossy())),
+    ];
+    args.extend(encoder.video_codec_args());
+    args.extend([
+        "-pix_fmt".into(),
+        "yuv420p".into(),
+        "-c:a".into(),
+        "copy".into(),
+        "-movflags".into(),
+        "+faststart".into(),
+        "-f".into(),
+        "mp4".into(),
+        "-y".into(),
+        output.to_string_lossy().to_string(),
+    ]);
+
+    let result = run_ffmpeg(app_handle, &args).await;
+    let _ = std::fs::remove_file(&overlay_path);
+    result.map(|_| (info.duration * 1000.0) as u64)
+}
+
+/// Cursor-free capture kept next to a recording so effects can be re-rendered
+pub fn clean_path(video: &Path) -> PathBuf {
+    video.with_extension("mp4.nocursor")
+}
+
+/// Render cursor effects into every part of a recording in place.
+///
+/// The first render moves the cursor-free capture aside; later renders start
+/// from it again, so styles can be changed without stacking cursors.
+pub async fn render_recording(
+    app_handle: &AppHandle,
+    parts: &[PathBuf],
+    telemetry: &Telemetry,
+    effects: &CursorEffects,
+    encoder: &EncoderProfile,
+) -> Result<()> {
+    let mut offset = 0;
+
+    for part in parts {
+        let clean = clean_path(part);
+        let source = if clean.exists() { clean.clone() } else { part.clone() };
+        let temp = part.with_extension("effects.tmp");
+
+        let duration = match render(app_handle, &source, &temp, telemetry, effects, offset, encoder).await {
+            Ok(duration) => duration,
+            Err(e) => {
+                let _ = std::fs::remove_file(&temp);
+                return Err(e);
+            }
+        };
+
+        if source == *part {
+            std::fs::rename(part, &clean).context("Failed to keep the cursor-free recording")?;
+        }
+        std::fs::rename(&temp, part).context("Failed to save the recording with cursor effects")?;
+        offset += duration;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+use tokio::sync::Mutex;
+
+use super::probe::parse_duration;
+use super::profile::EncoderProfile;
+use super::{escape_filter_path, stderr_tail};
+
+/// Lifecycle of an export job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A conversion of one recording to an encoder profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportJob {
+    pub id: u64,
+    pub source: String,
+    pub output: String,
+    pub profile: EncoderProfile,
+    pub status: JobStatus,
+    /// Fraction done, from 0.0 to 1.0
+    pub progress: f64,
+    pub attempts: u32,
+    pub error: Option<String>,
+    /// ASS script burned into the video, e.g. keystroke captions. Written
+    /// to a temporary file for each run of FFmpeg.
+    #[serde(skip)]
+    pub overlay: Option<String>,
+}
+
+impl ExportJob {
+    fn is_active(&self) -> bool {
+        matches!(self.status, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+/// FFmpeg export jobs, run in order with a concurrency limit
+pub struct ExportQueue {
+    jobs: Vec<ExportJob>,
+    children: HashMap<u64, CommandChild>,
+    /// Jobs whose FFmpeg was killed but hasn't exited yet, still holding a slot
+    stopping: HashSet<u64>,
+    next_id: u64,
+    concurrency: usize,
+}
+
+pub type SharedExportQueue = Arc<Mutex<ExportQueue>>;
+
+impl ExportQueue {
+    pub fn new() -> Self {
+        Self {
+            jobs: Vec::new(),
+            children: HashMap::new(),
+            stopping: HashSet::new(),
+            next_id: 0,
+            concurrency: 2,
+        }
+    }
+
+    /// All jobs, oldest first
+    pub fn jobs(&self) -> &[ExportJob] {
+        &self.jobs
+    }
+
+    /// Limit how many jobs run at the same time
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.concurrency = concurrency.max(1);
+    }
+
+    /// Whether `path` is read or written by a queued or running job
+    pub fn is_busy(&self, path: &str) -> bool {
+        self.jobs
+            .iter()
+            .any(|j| j.is_active() && (j.source == path || j.output == path))
+    }
+
+    /// Queue a conversion of `source` to `output`, optionally burning in an ASS `overlay`
+    pub fn enqueue(
+        &mut self,
+        source: PathBuf,
+        output: PathBuf,
+        profile: EncoderProfile,
+        overlay: Option<String>,
+    ) -> ExportJob {
+        self.next_id += 1;
+        let job = ExportJob {
+            id: self.next_id,
+            source: source.to_string_lossy().to_string(),
+            output: output.to_string_lossy().to_string(),
+            profile,
+            status: JobStatus::Queued,
+            progress: 0.0,
+            attempts: 0,
+            error: None,
+            overlay,
+        };
+        self.jobs.push(job.clone());
+        job
+    }
+
+    fn job_mut(&mut self, id: u64) -> Result<&mut ExportJob> {
+        self.jobs
+            .iter_mut()
+            .find(|j| j.id == id)
+            .ok_or_else(|| anyhow!("Export job {} not found", id))
+    }
+
+    /// Cancel a queued or running job; a running FFmpeg is killed
+    pub fn cancel(&mut self, id: u64) -> Result<ExportJob> {
+        let job = self.job_mut(id)?;
+        if !job.is_active() {
+            return Err(anyhow!("Export job {} is not running", id));
+        }
+        job.status = JobStatus::Cancelled;
+        let job = job.clone();
+
+        // The output is cleaned up and the slot freed once FFmpeg reports termination
+        if let Some(child) = self.children.remove(&id) {
+            self.stopping.insert(id);
+            let _ = child.kill();
+        }
+        Ok(job)
+    }
+
+    /// Queue a failed or cancelled job again
+    pub fn retry(&mut self, id: u64) -> Result<ExportJob> {
+        let job = self.job_mut(id)?;
+        if job.is_active() || job.status == JobStatus::Completed {
+            return Err(anyhow!("Only failed or cancelled export jobs can be retried"));
+        }
+        // A new FFmpeg would share the id, and the exit of the old one
+        // would be taken for its own
+        if self.stopping.contains(&id) {
+            return Err(anyhow!("Export job {} is still stopping, retry once it has", id));
+        }
+        let job = self.job_mut(id)?;
+        job.status = JobStatus::Queued;
+        job.progress = 0.0;
+        job.error = None;
+        Ok(job.clone())
+    }
+}
+
+impl Default for ExportQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Start queued jobs while below the concurrency limit
+pub async fn pump(app_handle: &AppHandle) {
+    let queue = app_handle.state::<SharedExportQueue>();
+    let mut queue = queue.lock().await;
+    start_queued(&mut queue, app_handle);
+}
+
+/// Start queued jobs of an already locked queue
+fn start_queued(queue: &mut ExportQueue, app_handle: &AppHandle) {
+    while queue.children.len() + queue.stopping.len() < queue.concurrency {
+        let Some(job) = queue.jobs.iter_mut().find(|j| j.status == JobStatus::Queued) else {
+            break;
+        };
+        job.status = JobStatus::Running;
+        job.attempts += 1;
+        let job = job.clone();
+
+        match spawn(app_handle, &job) {
+            Ok((rx, child)) => {
+                queue.children.insert(job.id, child);
+                tauri::async_runtime::spawn(watch(app_handle.clone(), job.id, rx));
+                let _ = app_handle.emit("export-job-updated", &job);
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(overlay_path(job.id));
+                if let Ok(job) = queue.job_mut(job.id) {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e.to_string());
+                    let _ = app_handle.emit("export-job-updated", &*job);
+                }
+            }
+        }
+    }
+}
+
+/// Temporary file the overlay of job `id` is written to while it runs
+fn overlay_path(id: u64) -> PathBuf {
+    std::env::temp_dir().join(format!("flashscreen-keys-{}-{}.ass", std::process::id(), id))
+}
+
+/// Spawn FFmpeg for `job`, reporting progress on stdout
+fn spawn(app_handle: &AppHandle, job: &ExportJob) -> Result<(Receiver<CommandEvent>, CommandChild)> {
+    let mut args: Vec<String> = vec![
+        String::from("-hide_banner"),
+        String::from("-nostats"),
+        String::from("-progress"),
+        String::from("pipe:1"),
+        String::from("-i"),
+        job.source.clone(),
+    ];
+    let overlay = match &job.overlay {
+        Some(script) => {
+            let path = overlay_path(job.id);
+            std::fs::write(&path, script).map_err(|e| anyhow!("Failed to write keystroke overlay: {}", e))?;
+            Some(format!("ass={}", escape_filter_path(&path.to_string_lossy())))
+        }
+        None => None,
+    };
+    args.extend(job.profile.export_args(overlay.as_deref()));
+    args.extend([String::from("-y"), job.output.clone()]);
+
+    app_handle
+        .shell()
+        .sidecar("ffmpeg")
+        .map_err(|e| anyhow!("Sidecar error: {}", e))?
+        .args(&args)
+        .spawn()
+        .map_err(|e| anyhow!("Failed to spawn FFmpeg: {}", e))
+}
+
+/// Follow a running job until FFmpeg exits
+async fn watch(app_handle: AppHandle, id: u64, mut rx: Receiver<CommandEvent>) {
+    let mut duration: Option<f64> = None;
+    let mut stderr = String::new();
+    let mut reported = 0.0;
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stderr(line) => {
+                let line = String::from_utf8_lossy(&line);
+                if duration.is_none() {
+                    duration = parse_duration(&line);
+                }
+                stderr.push_str(&line);
+                stderr.push('\n');
+            }
+            CommandEvent::Stdout(line) => {
+                // `-progress` reports the output position in microseconds
+                let line = String::from_utf8_lossy(&line);
+                let Some(position) = line.trim().strip_prefix("out_time_us=") else {
+                    continue;
+                };
+                let (Some(duration), Ok(position)) = (duration, position.parse::<f64>()) else {
+                    continue;
+                };
+
+                let progress = (position / 1_000_000.0 / duration).clamp(0.0, 1.0);
+                if progress - reported < 0.01 {
+                    continue;
+                }
+                reported = progress;
+
+                let queue = app_handle.state::<SharedExportQueue>();
+                let mut queue = queue.lock().await;
+                if let Ok(job) = queue.job_mut(id) {
+                    job.progress = progress;
+                    let _ = app_handle.emit("export-job-updated", &*job);
+                }
+            }
+            CommandEvent::Terminated(payload) => {
+                finish(&app_handle, id, payload.code == Some(0), &stderr).await;
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    // The events ended without an exit status, don't hold on to the slot
+    finish(&app_handle, id, false, &stderr).await;
+}
+
+/// Record the outcome of a job and remove output it didn't finish
+async fn finish(app_handle: &AppHandle, id: u64, success: bool, stderr: &str) {
+    let queue = app_handle.state::<SharedExportQueue>();
+    let mut queue = queue.lock().await;
+    queue.children.remove(&id);
+    queue.stopping.remove(&id);
+    let _ = std::fs::remove_file(overlay_path(id));
+
+    let Ok(job) = queue.job_mut(id) else {
+        return;
+    };
+
+    match job.status {
+        JobStatus::Running if success => {
+            job.status = JobStatus::Completed;
+            job.progress = 1.0;
+            let _ = app_handle.emit("recordings-changed", vec![job.output.clone()]);
+        }
+        JobStatus::Running => {
+            job.status = JobStatus::Failed;
+            job.error = Some(stderr_tail(stderr));
+            let _ = std::fs::remove_file(&job.output);
+        }
+        _ => {
+            let _ = std::fs::remove_file(&job.output);
+        }
+    }
+
+    let _ = app_handle.emit("export-job-updated", &*job);
+
+    // A slot is free again
+    start_queued(&mut queue, app_handle);
+}
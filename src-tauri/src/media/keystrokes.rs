@@ -0,0 +1,154 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::{ass_time, probe};
+use crate::recording::telemetry::Telemetry;
+
+/// Which key presses are recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeystrokeFilter {
+    /// Only combinations with Ctrl, Alt or Super, so typed text is never stored
+    ShortcutsOnly,
+    /// Every key, including plain typing
+    AllKeys,
+}
+
+/// Where the caption is placed on the video
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverlayPosition {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl OverlayPosition {
+    /// ASS `\an` alignment, numbered like a numpad
+    fn alignment(&self) -> u8 {
+        match self {
+            OverlayPosition::BottomLeft => 1,
+            OverlayPosition::BottomCenter => 2,
+            OverlayPosition::BottomRight => 3,
+            OverlayPosition::TopLeft => 7,
+            OverlayPosition::TopCenter => 8,
+            OverlayPosition::TopRight => 9,
+        }
+    }
+}
+
+/// Keystroke capture and the caption rendered from it at export
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct KeystrokeOverlay {
+    pub enabled: bool,
+    pub filter: KeystrokeFilter,
+    pub position: OverlayPosition,
+    /// Font size in pixels of a 1080p video
+    pub font_size: u32,
+    /// Colors as `#RRGGBB`
+    pub text_color: String,
+    pub background_color: String,
+    /// How long each caption stays on screen, in milliseconds
+    pub display_ms: u64,
+}
+
+impl Default for KeystrokeOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            filter: KeystrokeFilter::ShortcutsOnly,
+            position: OverlayPosition::BottomCenter,
+            font_size: 42,
+            text_color: String::from("#FFFFFF"),
+            background_color: String::from("#202020"),
+            display_ms: 1200,
+        }
+    }
+}
+
+/// `#RRGGBB` to the `&HAABBGGRR` order ASS styles use
+fn ass_color(color: &str, alpha: u8) -> String {
+    let hex = color.trim_start_matches('#');
+    let hex = if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) { hex } else { "FFFFFF" };
+    format!("&H{:02X}{}{}{}", alpha, &hex[4..6], &hex[2..4], &hex[0..2]).to_uppercase()
+}
+
+/// Build an ASS overlay captioning the recorded shortcuts of one recording part.
+///
+/// Captions are shortened when the next key follows, so they never stack.
+pub fn render_overlay(
+    telemetry: &Telemetry,
+    style: &KeystrokeOverlay,
+    width: u32,
+    height: u32,
+    offset: u64,
+    duration: u64,
+) -> String {
+    let font_size = style.font_size * height / 1080;
+    let margin = height / 20;
+
+    let mut ass = format!(
+        "[Script Info]\nScriptType: v4.00+\nPlayResX: {width}\nPlayResY: {height}\nScaledBorderAndShadow: yes\n\n\
+         [V4+ Styles]\n\
+         Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n\
+         Style: Keys,Arial,{font_size},{text},{text},{background},{background},1,0,0,0,100,100,0,0,3,{padding},0,{alignment},{margin},{margin},{margin},1\n\n\
+         [Events]\n\
+         Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+        text = ass_color(&style.text_color, 0),
+        background = ass_color(&style.background_color, 0x40),
+        padding = (font_size / 4).max(2),
+        alignment = style.position.alignment(),
+    );
+
+    let keys: Vec<_> = telemetry
+        .keys
+        .iter()
+        .filter(|k| k.t >= offset && k.t <= offset + duration)
+        .collect();
+
+    for (i, key) in keys.iter().enumerate() {
+        let start = key.t - offset;
+        let mut end = start + style.display_ms;
+        if let Some(next) = keys.get(i + 1) {
+            end = end.min(next.t - offset);
+        }
+
+        // Braces would start an override block
+        let label = key.label.replace('{', "(").replace('}', ")");
+        let _ = writeln!(ass, "Dialogue: 0,{},{},Keys,,0,0,0,,{}", ass_time(start), ass_time(end), label);
+    }
+
+    ass
+}
+
+/// Render the keystroke overlay for `source` as an ASS script.
+///
+/// `earlier_parts` are the parts recorded before `source`, whose durations
+/// place it in the telemetry. Returns `None` when no keys fall into it.
+pub async fn prepare_overlay(
+    app_handle: &AppHandle,
+    source: &Path,
+    earlier_parts: &[PathBuf],
+    telemetry: &Telemetry,
+    style: &KeystrokeOverlay,
+) -> Result<Option<String>> {
+    let mut offset = 0;
+    for part in earlier_parts {
+        offset += (probe::probe(app_handle, part).await?.duration * 1000.0) as u64;
+    }
+
+    let info = probe::probe(app_handle, source).await?;
+    let duration = (info.duration * 1000.0) as u64;
+    if info.width == 0 || !telemetry.keys.iter().any(|k| k.t >= offset && k.t <= offset + duration) {
+        return Ok(None);
+    }
+
+    Ok(Some(render_overlay(telemetry, style, info.width, info.height, offset, duration)))
+}
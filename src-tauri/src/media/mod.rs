@@ -0,0 +1,75 @@
+pub mod animation;
+pub mod audio;
+pub mod capabilities;
+pub mod effects;
+pub mod jobs;
+pub mod keystrokes;
+pub mod probe;
+pub mod profile;
+pub mod trim;
+pub mod zoom;
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+
+use crate::error::AppError;
+use crate::logging;
+
+/// Run the FFmpeg sidecar to completion, returning its stderr
+pub async fn run_ffmpeg(app_handle: &AppHandle, args: &[String]) -> Result<String> {
+    tracing::debug!("Running {}", logging::command_line(args));
+    let output = app_handle
+        .shell()
+        .sidecar("ffmpeg")
+        .map_err(|e| AppError::FfmpegMissing { message: e.to_string() })?
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| AppError::FfmpegMissing { message: e.to_string() })?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if !output.status.success() {
+        tracing::warn!("{} failed:\n{}", logging::command_line(args), stderr.trim_end());
+        return Err(AppError::FfmpegFailed { stderr: stderr_tail(&stderr) }.into());
+    }
+
+    Ok(stderr)
+}
+
+/// Last few lines of FFmpeg's stderr, where the actual error usually is
+pub fn stderr_tail(stderr: &str) -> String {
+    let lines: Vec<&str> = stderr.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines[lines.len().saturating_sub(5)..].join("\n")
+}
+
+/// Pick `<stem>_<suffix>.<ext>` next to `path`, counting up while `is_taken`
+pub fn derived_path(
+    path: &Path,
+    suffix: &str,
+    extension: &str,
+    is_taken: impl Fn(&Path) -> bool,
+) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let parent = path.parent().unwrap_or(Path::new(""));
+
+    let mut candidate = parent.join(format!("{}_{}.{}", stem, suffix, extension));
+    let mut n = 2;
+    while candidate.exists() || is_taken(&candidate) {
+        candidate = parent.join(format!("{}_{}_{}.{}", stem, suffix, n, extension));
+        n += 1;
+    }
+    candidate
+}
+
+/// Escape a path for use as a filter option value
+pub fn escape_filter_path(path: &str) -> String {
+    path.replace('\\', "/").replace(':', "\\\\:").replace(',', "\\,")
+}
+
+/// ASS timestamp, `h:mm:ss.cc`
+pub fn ass_time(ms: u64) -> String {
+    let cs = ms / 10;
+    format!("{}:{:02}:{:02}.{:02}", cs / 360_000, cs / 6000 % 60, cs / 100 % 60, cs % 100)
+}
@@ -0,0 +1,189 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::AppHandle;
+
+use super::run_ffmpeg;
+
+/// An audio stream of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioTrack {
+    /// Position among the audio streams, as in `0:a:N`
+    pub index: usize,
+    pub codec: String,
+    /// Source name written by the recorder, e.g. `Microphone`
+    pub title: Option<String>,
+}
+
+/// Timing information of a recording
+#[derive(Debug, Clone, Default)]
+pub struct MediaInfo {
+    /// Duration in seconds
+    pub duration: f64,
+    /// Presentation times of the video keyframes in seconds, ascending
+    pub keyframes: Vec<f64>,
+    /// Size of the first video stream in pixels
+    pub width: u32,
+    pub height: u32,
+    /// Average frame rate of the first video stream, 0 when unknown
+    pub frame_rate: f64,
+    /// Codec of the first video stream, e.g. `h264`
+    pub video_codec: Option<String>,
+    /// Pixel format of the first video stream, e.g. `yuv420p`
+    pub pixel_format: Option<String>,
+    pub audio_tracks: Vec<AudioTrack>,
+}
+
+impl MediaInfo {
+    /// First keyframe at or after `time`
+    pub fn keyframe_at_or_after(&self, time: f64) -> Option<f64> {
+        self.keyframes.iter().copied().find(|k| *k >= time - KEYFRAME_TOLERANCE)
+    }
+}
+
+/// Times closer than this are treated as the same frame
+pub const KEYFRAME_TOLERANCE: f64 = 0.001;
+
+/// Parse `Duration: HH:MM:SS.ss` from FFmpeg's input banner
+pub fn parse_duration(stderr: &str) -> Option<f64> {
+    let line = stderr.lines().find(|l| l.trim_start().starts_with("Duration:"))?;
+    let value = line.trim_start().strip_prefix("Duration:")?.split(',').next()?.trim();
+
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+/// Parse `WxH` from the first `Video:` stream line of FFmpeg's input banner
+pub fn parse_video_size(stderr: &str) -> Option<(u32, u32)> {
+    let line = stderr.lines().find(|l| l.contains("Video:"))?;
+
+    line.split([',', ' '])
+        .filter_map(|token| {
+            let (w, h) = token.split_once('x')?;
+            Some((w.parse().ok()?, h.parse().ok()?))
+        })
+        .find(|(w, h)| *w > 0 && *h > 0)
+}
+
+/// Parse the codec and pixel format from the first `Video:` stream line of
+/// FFmpeg's input banner, e.g. `Video: h264 (High) (avc1), yuv420p(tv, progressive)`
+pub fn parse_video_format(stderr: &str) -> (Option<String>, Option<String>) {
+    let Some((_, rest)) = stderr.lines().find_map(|l| l.split_once("Video: ")) else {
+        return (None, None);
+    };
+    let mut parts = rest.split(", ");
+    let codec = parts.next().and_then(|p| p.split_whitespace().next()).map(String::from);
+    let pixel_format = parts
+        .next()
+        .and_then(|p| p.split(['(', ' ']).next())
+        .filter(|p| !p.is_empty())
+        .map(String::from);
+    (codec, pixel_format)
+}
+
+/// Parse `N fps` from the first `Video:` stream line of FFmpeg's input banner
+pub fn parse_frame_rate(stderr: &str) -> Option<f64> {
+    let line = stderr.lines().find(|l| l.contains("Video:"))?;
+    let value = line.split(',').find_map(|part| part.trim().strip_suffix(" fps"))?;
+    value.trim().parse().ok()
+}
+
+/// Parse the audio streams and their names from FFmpeg's input banner
+pub fn parse_audio_tracks(stderr: &str) -> Vec<AudioTrack> {
+    let mut tracks: Vec<AudioTrack> = Vec::new();
+    let mut in_audio = false;
+
+    for line in stderr.lines() {
+        let line = line.trim();
+        // Everything after the input banner describes the outputs
+        if line.starts_with("Output #") || line.starts_with("Stream mapping") {
+            break;
+        }
+
+        if line.starts_with("Stream #") {
+            in_audio = false;
+            if let Some(rest) = line.split_once("Audio: ").map(|(_, r)| r) {
+                in_audio = true;
+                tracks.push(AudioTrack {
+                    index: tracks.len(),
+                    codec: rest.split([' ', ',']).next().unwrap_or_default().to_string(),
+                    title: None,
+                });
+            }
+            continue;
+        }
+
+        // Stream metadata follows its stream line
+        let Some(track) = tracks.last_mut().filter(|_| in_audio) else {
+            continue;
+        };
+        if let Some((key, value)) = line.split_once(':') {
+            let (key, value) = (key.trim(), value.trim());
+            // MP4 files without a name carry the muxer's default handler
+            if (key == "title" || key == "handler_name") && !value.is_empty() && value != "SoundHandler" {
+                track.title = Some(value.to_string());
+            }
+        }
+    }
+
+    tracks
+}
+
+/// Parse the `pts_time:` values printed by the `showinfo` filter
+fn parse_showinfo_times(stderr: &str) -> Vec<f64> {
+    let mut times: Vec<f64> = stderr
+        .lines()
+        .filter(|l| l.contains("Parsed_showinfo"))
+        .filter_map(|l| {
+            let rest = &l[l.find("pts_time:")? + "pts_time:".len()..];
+            rest.split_whitespace().next()?.parse().ok()
+        })
+        .collect();
+    times.sort_by(|a, b| a.total_cmp(b));
+    times
+}
+
+/// Read duration and keyframe positions of `path` with the FFmpeg sidecar
+pub async fn probe(app_handle: &AppHandle, path: &Path) -> Result<MediaInfo> {
+    // Only keyframes are decoded, so this is fast even for long recordings
+    let args: Vec<String> = [
+        "-hide_banner",
+        "-skip_frame",
+        "nokey",
+        "-i",
+        &path.to_string_lossy(),
+        "-map",
+        "0:v:0",
+        "-vf",
+        "showinfo",
+        "-an",
+        "-f",
+        "null",
+        "-",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    let stderr = run_ffmpeg(app_handle, &args).await?;
+    let duration = parse_duration(&stderr)
+        .ok_or_else(|| anyhow!("Could not read the duration of {}", path.display()))?;
+
+    let (width, height) = parse_video_size(&stderr).unwrap_or_default();
+    let (video_codec, pixel_format) = parse_video_format(&stderr);
+
+    Ok(MediaInfo {
+        duration,
+        keyframes: parse_showinfo_times(&stderr),
+        width,
+        height,
+        frame_rate: parse_frame_rate(&stderr).unwrap_or_default(),
+        video_codec,
+        pixel_format,
+        audio_tracks: parse_audio_tracks(&stderr),
+    })
+}
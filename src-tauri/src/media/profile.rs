@@ -0,0 +1,336 @@
+use serde::{Deserialize, Serialize};
+
+/// Profile used for live capture when none is configured
+pub const DEFAULT_PROFILE: &str = "realtime";
+
+/// Presets of libx264 and libx265, fastest first
+const X26X_PRESETS: &[&str] = &[
+    "ultrafast", "superfast", "veryfast", "faster", "fast", "medium", "slow", "slower", "veryslow",
+];
+
+/// Encoders sharing how speed and quality are set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EncoderFamily {
+    /// libx264 and libx265: `-preset`, `-crf` 0-51
+    X26x,
+    /// NVIDIA: `-preset` p1-p7, `-cq` 0-51
+    Nvenc,
+    /// Intel Quick Sync: `-preset` veryfast-veryslow, `-global_quality` 1-51
+    Qsv,
+    /// AMD: `-quality` speed/balanced/quality, constant `-qp_i`/`-qp_p` 0-51
+    Amf,
+    /// Apple VideoToolbox: no speed option, `-q:v` 1-100 (higher is better)
+    VideoToolbox,
+    /// libsvtav1: `-preset` 13-0, `-crf` 0-63
+    SvtAv1,
+    /// libaom-av1: `-cpu-used` 8-0, `-crf` 0-63
+    AomAv1,
+    /// librav1e: `-speed` 10-0, `-qp` 0-255
+    Rav1e,
+    /// Encoders without these options, e.g. `gif` or `h264_mf`
+    Other,
+}
+
+impl EncoderFamily {
+    fn of(codec: &str) -> Self {
+        match codec {
+            "libx264" | "libx265" => Self::X26x,
+            "libsvtav1" => Self::SvtAv1,
+            "libaom-av1" => Self::AomAv1,
+            "librav1e" => Self::Rav1e,
+            c if c.ends_with("_nvenc") => Self::Nvenc,
+            c if c.ends_with("_qsv") => Self::Qsv,
+            c if c.ends_with("_amf") => Self::Amf,
+            c if c.ends_with("_videotoolbox") => Self::VideoToolbox,
+            _ => Self::Other,
+        }
+    }
+
+    /// Values of the speed option, fastest first
+    fn speeds(self) -> Vec<String> {
+        let numbers = |range: std::ops::RangeInclusive<u32>| range.rev().map(|n| n.to_string()).collect();
+        match self {
+            Self::X26x => X26X_PRESETS.iter().map(|p| p.to_string()).collect(),
+            Self::Nvenc => (1..=7).map(|n| format!("p{}", n)).collect(),
+            Self::Qsv => X26X_PRESETS[2..].iter().map(|p| p.to_string()).collect(),
+            Self::Amf => ["speed", "balanced", "quality"].iter().map(|p| p.to_string()).collect(),
+            Self::SvtAv1 => numbers(0..=13),
+            Self::AomAv1 => numbers(0..=8),
+            Self::Rav1e => numbers(0..=10),
+            Self::VideoToolbox | Self::Other => Vec::new(),
+        }
+    }
+
+    /// Largest value of the quality option, where higher means smaller files,
+    /// except for VideoToolbox
+    fn max_quality(self) -> Option<u32> {
+        match self {
+            Self::X26x | Self::Nvenc | Self::Qsv | Self::Amf => Some(51),
+            Self::SvtAv1 | Self::AomAv1 => Some(63),
+            Self::Rav1e => Some(255),
+            Self::VideoToolbox => Some(100),
+            Self::Other => None,
+        }
+    }
+
+    /// Quality as a fraction from 0 (best) to 1 (smallest)
+    fn quality_fraction(self, value: u32) -> Option<f64> {
+        let max = self.max_quality()? as f64;
+        let fraction = (value as f64 / max).clamp(0.0, 1.0);
+        Some(if self == Self::VideoToolbox { 1.0 - fraction } else { fraction })
+    }
+
+    fn quality_value(self, fraction: f64) -> Option<u32> {
+        let max = self.max_quality()? as f64;
+        let fraction = if self == Self::VideoToolbox { 1.0 - fraction } else { fraction };
+        Some((fraction * max).round().max(1.0) as u32)
+    }
+
+    /// Arguments setting the speed `preset` and the quality `crf`, both in
+    /// this family's own scale
+    fn args(self, preset: Option<&str>, crf: Option<u32>) -> Vec<String> {
+        let speed = match self {
+            Self::Amf => "-quality",
+            Self::AomAv1 => "-cpu-used",
+            Self::Rav1e => "-speed",
+            _ => "-preset",
+        };
+        let mut args: Vec<String> = preset.into_iter().flat_map(|p| [speed.to_string(), p.to_string()]).collect();
+
+        let Some(crf) = crf.map(|c| c.to_string()) else {
+            return args;
+        };
+        let crf = crf.as_str();
+        let quality: Vec<&str> = match self {
+            Self::X26x | Self::SvtAv1 | Self::Other => vec!["-crf", crf],
+            Self::Nvenc => vec!["-cq", crf],
+            Self::Qsv => vec!["-global_quality", crf],
+            Self::Amf => vec!["-rc", "cqp", "-qp_i", crf, "-qp_p", crf],
+            Self::VideoToolbox => vec!["-q:v", crf],
+            // Constant quality only when the bitrate is left open
+            Self::AomAv1 => vec!["-b:v", "0", "-crf", crf],
+            Self::Rav1e => vec!["-qp", crf],
+        };
+        args.extend(quality.into_iter().map(String::from));
+        args
+    }
+}
+
+/// Encoder settings for a target format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderProfile {
+    pub id: String,
+    pub name: String,
+    /// Extension of output files, e.g. `mp4`
+    pub extension: String,
+    /// FFmpeg muxer, e.g. `mp4`
+    pub format: String,
+    /// Video encoder, `None` drops the video stream
+    pub video_codec: Option<String>,
+    pub preset: Option<String>,
+    pub tune: Option<String>,
+    pub crf: Option<u32>,
+    /// Downscale to at most this height, keeping the aspect ratio
+    pub max_height: Option<u32>,
+    pub fps: Option<u32>,
+    /// Extra filters appended to the video filter chain
+    pub filter: Option<String>,
+    /// Audio encoder, `None` drops the audio streams
+    pub audio_codec: Option<String>,
+    pub audio_bitrate: Option<String>,
+}
+
+impl EncoderProfile {
+    /// Profiles shipped with the app
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self {
+                id: String::from(DEFAULT_PROFILE),
+                name: String::from("Real-time H.264"),
+                extension: String::from("mp4"),
+                format: String::from("mp4"),
+                video_codec: Some(String::from("libx264")),
+                preset: Some(String::from("ultrafast")), // Fast encoding for real-time
+                tune: Some(String::from("zerolatency")),
+                crf: Some(23),
+                max_height: None,
+                fps: None,
+                filter: None,
+                audio_codec: Some(String::from("aac")),
+                audio_bitrate: Some(String::from("128k")),
+            },
+            Self {
+                id: String::from("web-720p"),
+                name: String::from("720p Web MP4"),
+                extension: String::from("mp4"),
+                format: String::from("mp4"),
+                video_codec: Some(String::from("libx264")),
+                preset: Some(String::from("medium")),
+                tune: None,
+                crf: Some(23),
+                max_height: Some(720),
+                fps: None,
+                filter: None,
+                audio_codec: Some(String::from("aac")),
+                audio_bitrate: Some(String::from("128k")),
+            },
+            Self {
+                id: String::from("av1"),
+                name: String::from("AV1 MP4"),
+                extension: String::from("mp4"),
+                format: String::from("mp4"),
+                video_codec: Some(String::from("libsvtav1")),
+                preset: Some(String::from("8")),
+                tune: None,
+                crf: Some(35),
+                max_height: None,
+                fps: None,
+                filter: None,
+                audio_codec: Some(String::from("aac")),
+                audio_bitrate: Some(String::from("128k")),
+            },
+            Self {
+                id: String::from("gif"),
+                name: String::from("Animated GIF"),
+                extension: String::from("gif"),
+                format: String::from("gif"),
+                video_codec: Some(String::from("gif")),
+                preset: None,
+                tune: None,
+                crf: None,
+                max_height: Some(480),
+                fps: Some(12),
+                filter: Some(String::from("split[a][b];[a]palettegen[p];[b][p]paletteuse")),
+                audio_codec: None,
+                audio_bitrate: None,
+            },
+            Self {
+                id: String::from("audio-only"),
+                name: String::from("Audio only (M4A)"),
+                extension: String::from("m4a"),
+                format: String::from("ipod"),
+                video_codec: None,
+                preset: None,
+                tune: None,
+                crf: None,
+                max_height: None,
+                fps: None,
+                filter: None,
+                audio_codec: Some(String::from("aac")),
+                audio_bitrate: Some(String::from("192k")),
+            },
+        ]
+    }
+
+    /// Look up a built-in profile by id
+    pub fn find(id: &str) -> Option<Self> {
+        Self::builtin().into_iter().find(|p| p.id == id)
+    }
+
+    /// Look up a profile, falling back to the live capture default
+    pub fn find_or_default(id: &str) -> Self {
+        Self::find(id)
+            .or_else(|| Self::find(DEFAULT_PROFILE))
+            .unwrap_or_else(|| Self::builtin().remove(0))
+    }
+
+    /// Video encoder arguments, or `-vn`
+    pub fn video_codec_args(&self) -> Vec<String> {
+        let Some(codec) = &self.video_codec else {
+            return vec![String::from("-vn")];
+        };
+
+        let mut args = vec![String::from("-c:v"), codec.clone()];
+        if let Some(tune) = &self.tune {
+            args.extend([String::from("-tune"), tune.clone()]);
+        }
+        args.extend(EncoderFamily::of(codec).args(self.preset.as_deref(), self.crf));
+        args
+    }
+
+    /// This profile encoding video with `codec` instead, with the speed and
+    /// quality translated to its options
+    pub fn with_video_codec(&self, codec: &str) -> Self {
+        let from = self.video_codec.as_deref().map_or(EncoderFamily::Other, EncoderFamily::of);
+        let to = EncoderFamily::of(codec);
+        let mut profile = self.clone();
+        profile.video_codec = Some(codec.to_string());
+        if from == to && to != EncoderFamily::Other {
+            return profile;
+        }
+
+        // Same position on the speed scale, e.g. ultrafast is p1 on NVENC
+        let (from_speeds, to_speeds) = (from.speeds(), to.speeds());
+        profile.preset = self
+            .preset
+            .as_ref()
+            .and_then(|preset| from_speeds.iter().position(|p| p == preset))
+            .filter(|_| !to_speeds.is_empty())
+            .map(|i| {
+                let fraction = i as f64 / (from_speeds.len() - 1).max(1) as f64;
+                to_speeds[(fraction * (to_speeds.len() - 1) as f64).round() as usize].clone()
+            });
+        profile.crf = self
+            .crf
+            .and_then(|crf| from.quality_fraction(crf))
+            .and_then(|fraction| to.quality_value(fraction));
+        // Low latency is spelled differently by every family
+        profile.tune = match (self.tune.as_deref(), to) {
+            (Some("zerolatency"), EncoderFamily::X26x) => Some(String::from("zerolatency")),
+            (Some("zerolatency"), EncoderFamily::Nvenc) => Some(String::from("ull")),
+            _ => None,
+        };
+        profile
+    }
+
+    /// Audio encoder arguments, or `-an`
+    pub fn audio_codec_args(&self) -> Vec<String> {
+        let Some(codec) = &self.audio_codec else {
+            return vec![String::from("-an")];
+        };
+
+        let mut args = vec![String::from("-c:a"), codec.clone()];
+        if let Some(bitrate) = &self.audio_bitrate {
+            args.extend([String::from("-b:a"), bitrate.clone()]);
+        }
+        args
+    }
+
+    /// Video filter chain for converting an existing recording, starting
+    /// with `overlay` so it is drawn at the source size
+    pub fn video_filter(&self, overlay: Option<&str>) -> Option<String> {
+        self.video_codec.as_ref()?;
+
+        let mut filters: Vec<String> = overlay.map(String::from).into_iter().collect();
+        if let Some(fps) = self.fps {
+            filters.push(format!("fps={}", fps));
+        }
+        if let Some(height) = self.max_height {
+            filters.push(format!("scale=-2:'min({},ih)':flags=lanczos", height));
+        }
+        if self.format == "mp4" {
+            filters.push(String::from("format=yuv420p"));
+        }
+        if let Some(filter) = &self.filter {
+            filters.push(filter.clone());
+        }
+
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+
+    /// Output arguments for converting an existing recording, without the path
+    pub fn export_args(&self, overlay: Option<&str>) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(filter) = self.video_filter(overlay) {
+            args.extend([String::from("-vf"), filter]);
+        }
+        args.extend(self.video_codec_args());
+        args.extend(self.audio_codec_args());
+        if self.format == "mp4" || self.format == "ipod" {
+            args.extend([String::from("-movflags"), String::from("+faststart")]);
+        }
+        args.extend([String::from("-f"), self.format.clone()]);
+        args
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::capabilities;
+use super::probe::{self, MediaInfo, KEYFRAME_TOLERANCE};
+use super::profile::{EncoderProfile, DEFAULT_PROFILE};
+use super::run_ffmpeg;
+
+/// A time range to keep, in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// One piece of the trimmed output
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Segment {
+    /// Starts on a keyframe, copied without re-encoding
+    Copy(TimeRange),
+    /// Boundary GOP that has to be re-encoded to cut precisely
+    Encode(TimeRange),
+}
+
+/// Sort `ranges`, clamp them to the recording and reject invalid or overlapping ones
+pub fn normalize_ranges(mut ranges: Vec<TimeRange>, duration: f64) -> Result<Vec<TimeRange>> {
+    if ranges.is_empty() {
+        return Err(anyhow!("No time range to keep"));
+    }
+
+    for range in ranges.iter_mut() {
+        range.end = range.end.min(duration);
+        if !range.start.is_finite() || range.start < 0.0 || range.start >= range.end {
+            return Err(anyhow!(
+                "Invalid time range {:.3}-{:.3} for a {:.3}s recording",
+                range.start,
+                range.end,
+                duration
+            ));
+        }
+    }
+
+    ranges.sort_by(|a, b| a.start.total_cmp(&b.start));
+    if let Some(w) = ranges.windows(2).find(|w| w[1].start < w[0].end) {
+        return Err(anyhow!(
+            "Time ranges {:.3}-{:.3} and {:.3}-{:.3} overlap",
+            w[0].start,
+            w[0].end,
+            w[1].start,
+            w[1].end
+        ));
+    }
+
+    Ok(ranges)
+}
+
+/// Split every range into a re-encoded head up to the next keyframe and a copied rest
+fn plan(ranges: &[TimeRange], info: &MediaInfo) -> Vec<Segment> {
+    let mut segments = Vec::new();
+
+    for range in ranges {
+        match info.keyframe_at_or_after(range.start) {
+            Some(k) if k <= range.start + KEYFRAME_TOLERANCE => {
+                segments.push(Segment::Copy(*range));
+            }
+            Some(k) if k < range.end - KEYFRAME_TOLERANCE => {
+                segments.push(Segment::Encode(TimeRange { start: range.start, end: k }));
+                segments.push(Segment::Copy(TimeRange { start: k, end: range.end }));
+            }
+            _ => segments.push(Segment::Encode(*range)),
+        }
+    }
+
+    segments
+}
+
+/// Encoder arguments for re-encoded heads, matching the codec and pixel
+/// format of the copied rest so the pieces can be joined.
+///
+/// The codec's software encoder is preferred, standing in with another
+/// encoder of the codec where the FFmpeg build lacks it.
+async fn encode_args(app_handle: &AppHandle, info: &MediaInfo) -> Result<Vec<String>> {
+    let codec = match info.video_codec.as_deref() {
+        Some("h264") => "libx264",
+        Some("hevc") => "libx265",
+        Some("av1") => "libsvtav1",
+        codec => return Err(anyhow!("Can't cut {} video precisely", codec.unwrap_or("unknown"))),
+    };
+    let encoder = capabilities::encoder(app_handle, codec).await?;
+
+    // Near-lossless and fast, in libx264 terms translated for the encoder
+    let profile = EncoderProfile {
+        video_codec: Some(String::from("libx264")),
+        preset: Some(String::from("veryfast")),
+        tune: None,
+        crf: Some(18),
+        ..EncoderProfile::find_or_default(DEFAULT_PROFILE)
+    }
+    .with_video_codec(&encoder);
+
+    let mut args = profile.video_codec_args();
+    if let Some(pixel_format) = &info.pixel_format {
+        args.extend(["-pix_fmt".into(), pixel_format.clone()]);
+    }
+    // Audio packets are short enough to cut where they fall, and copying
+    // keeps the audio identical across the joins
+    args.extend(["-c:a".into(), "copy".into()]);
+    Ok(args)
+}
+
+/// FFmpeg arguments writing `segment` of `input` to `output` in `format`,
+/// re-encoding with `encode` where needed
+fn segment_args(input: &Path, segment: Segment, encode: &[String], output: &Path, format: &str) -> Vec<String> {
+    let (range, codec): (TimeRange, Vec<String>) = match segment {
+        Segment::Copy(range) => (
+            range,
+            ["-c", "copy", "-avoid_negative_ts", "make_zero"].iter().map(|s| s.to_string()).collect(),
+        ),
+        Segment::Encode(range) => (range, encode.to_vec()),
+    };
+
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-ss".into(),
+        format!("{:.6}", range.start),
+        "-i".into(),
+        input.to_string_lossy().to_string(),
+        "-t".into(),
+        format!("{:.6}", range.end - range.start),
+        "-map".into(),
+        "0".into(),
+    ];
+    args.extend(codec);
+    if format == "mp4" {
+        args.extend(["-movflags".into(), "+faststart".into()]);
+    }
+    args.extend(["-f".into(), format.to_string(), "-y".into()]);
+    args.push(output.to_string_lossy().to_string());
+    args
+}
+
+/// Keep `ranges` of `input` and write the result to `output`.
+///
+/// Ranges starting on a keyframe are stream copied; otherwise only the part
+/// up to the next keyframe is re-encoded and the pieces are concatenated.
+pub async fn trim(app_handle: &AppHandle, input: &Path, output: &Path, ranges: Vec<TimeRange>) -> Result<()> {
+    let info = probe::probe(app_handle, input).await?;
+    let ranges = normalize_ranges(ranges, info.duration)?;
+    let segments = plan(&ranges, &info);
+    let encode = if segments.iter().any(|s| matches!(s, Segment::Encode(_))) {
+        encode_args(app_handle, &info).await?
+    } else {
+        Vec::new()
+    };
+
+    // A single piece needs no intermediate files
+    if let [segment] = segments.as_slice() {
+        run_ffmpeg(app_handle, &segment_args(input, *segment, &encode, output, "mp4")).await?;
+        return Ok(());
+    }
+
+    // Next to the output, where there is room for a copy of the recording
+    let name = output.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp_dir = output.with_file_name(format!("{}.segments", name));
+    std::fs::create_dir_all(&temp_dir).context("Failed to create temporary folder")?;
+
+    let result = concat_segments(app_handle, input, output, &segments, &encode, &temp_dir).await;
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    result
+}
+
+/// Write every segment to `temp_dir` and join them with the concat demuxer.
+///
+/// The pieces are MPEG-TS, which repeats the codec parameters in the
+/// stream, so the re-encoded heads play even where their parameters differ
+/// from the copied parts.
+async fn concat_segments(
+    app_handle: &AppHandle,
+    input: &Path,
+    output: &Path,
+    segments: &[Segment],
+    encode: &[String],
+    temp_dir: &Path,
+) -> Result<()> {
+    let mut list = String::new();
+
+    for (i, segment) in segments.iter().enumerate() {
+        // Not `.mp4`, so a library scan during the trim doesn't list it
+        let path: PathBuf = temp_dir.join(format!("segment_{:03}.tmp", i));
+        run_ffmpeg(app_handle, &segment_args(input, *segment, encode, &path, "mpegts")).await?;
+
+        // Quote for the concat list, escaping single quotes
+        let quoted = path.to_string_lossy().replace('\'', r"'\''");
+        list.push_str(&format!("file '{}'\n", quoted));
+    }
+
+    let list_path = temp_dir.join("segments.txt");
+    std::fs::write(&list_path, list).context("Failed to write segment list")?;
+
+    let args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-f".into(),
+        "concat".into(),
+        "-safe".into(),
+        "0".into(),
+        "-i".into(),
+        list_path.to_string_lossy().to_string(),
+        "-map".into(),
+        "0".into(),
+        "-c".into(),
+        "copy".into(),
+        "-movflags".into(),
+        "+faststart".into(),
+        "-f".into(),
+        "mp4".into(),
+        "-y".into(),
+        output.to_string_lossy().to_string(),
+    ];
+    run_ffmpeg(app_handle, &args).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: f64, end: f64) -> TimeRange {
+        TimeRange { start, end }
+    }
+
+    #[test]
+    fn sorts_and_clamps_ranges() {
+        let ranges = normalize_ranges(vec![range(5.0, 20.0), range(0.0, 2.0)], 10.0).unwrap();
+        assert_eq!(ranges, [range(0.0, 2.0), range(5.0, 10.0)]);
+    }
+
+    #[test]
+    fn rejects_empty_and_invalid_ranges() {
+        assert!(normalize_ranges(Vec::new(), 10.0).is_err());
+        assert!(normalize_ranges(vec![range(3.0, 3.0)], 10.0).is_err());
+        assert!(normalize_ranges(vec![range(-1.0, 3.0)], 10.0).is_err());
+        assert!(normalize_ranges(vec![range(f64::NAN, 3.0)], 10.0).is_err());
+        // Starts after the end of the recording
+        assert!(normalize_ranges(vec![range(12.0, 15.0)], 10.0).is_err());
+    }
+
+    #[test]
+    fn rejects_overlapping_ranges() {
+        assert!(normalize_ranges(vec![range(4.0, 8.0), range(0.0, 5.0)], 10.0).is_err());
+        assert!(normalize_ranges(vec![range(0.0, 5.0), range(5.0, 8.0)], 10.0).is_ok());
+    }
+
+    #[test]
+    fn re_encodes_only_up_to_the_next_keyframe() {
+        let info = MediaInfo {
+            keyframes: vec![0.0, 2.0, 4.0],
+            ..MediaInfo::default()
+        };
+        let segments = plan(&[range(0.0, 1.5), range(2.5, 6.0), range(4.5, 4.8)], &info);
+        assert_eq!(
+            segments,
+            [
+                Segment::Copy(range(0.0, 1.5)),
+                Segment::Encode(range(2.5, 4.0)),
+                Segment::Copy(range(4.0, 6.0)),
+                Segment::Encode(range(4.5, 4.8)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::probe;
+use super::profile::EncoderProfile;
+use super::run_ffmpeg;
+use crate::recording::telemetry::{session_stem, CursorEventKind, Telemetry};
+
+/// Version of the zoom override file format
+const ZOOM_VERSION: u32 = 1;
+
+/// Largest zoom factor, beyond this the capture is too blurry to read
+const MAX_ZOOM: f64 = 8.0;
+
+/// Automatic zoom toward click areas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AutoZoom {
+    /// Render a zoomed copy after each recording
+    pub enabled: bool,
+    /// Zoom factor around clicks, 2 shows half the width
+    pub scale: f64,
+    /// How long the view stays zoomed after the last click, in milliseconds
+    pub hold_ms: u64,
+    /// Duration of zooming in, out and panning, in milliseconds
+    pub transition_ms: u64,
+}
+
+impl Default for AutoZoom {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scale: 2.0,
+            hold_ms: 1500,
+            transition_ms: 600,
+        }
+    }
+}
+
+/// Point of the zoom timeline; the view eases between consecutive points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoomKeyframe {
+    /// Milliseconds since the recording started
+    pub t: u64,
+    /// 1 shows the whole frame
+    pub zoom: f64,
+    /// Center of the view relative to the frame, 0 to 1
+    pub x: f64,
+    pub y: f64,
+    /// Placed by hand; kept when the timeline is regenerated
+    #[serde(default)]
+    pub manual: bool,
+}
+
+impl ZoomKeyframe {
+    fn auto(t: u64, zoom: f64, (x, y): (f64, f64)) -> Self {
+        Self { t, zoom, x, y, manual: false }
+    }
+}
+
+/// Manual zoom points of a recording, stored as `<stem>.zoom.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoomOverrides {
+    pub version: u32,
+    pub points: Vec<ZoomKeyframe>,
+}
+
+impl ZoomOverrides {
+    pub fn new(points: Vec<ZoomKeyframe>) -> Self {
+        Self {
+            version: ZOOM_VERSION,
+            points: points.into_iter().map(|p| ZoomKeyframe { manual: true, ..p }).collect(),
+        }
+    }
+
+    /// Overrides of a recording or one of its parts, empty when none were saved
+    pub fn load_for(video: &Path) -> Result<Self> {
+        let Some(path) = overrides_path(video).filter(|p| p.exists()) else {
+            return Ok(Self::new(Vec::new()));
+        };
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Save next to `video`, removing the file when there are no points
+    pub fn save_for(&self, video: &Path) -> Result<()> {
+        let path = overrides_path(video).ok_or_else(|| anyhow!("Invalid recording path"))?;
+        if self.points.is_empty() {
+            if path.exists() {
+                std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+            return Ok(());
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Zoom override file of the session `video` belongs to
+pub fn overrides_path(video: &Path) -> Option<PathBuf> {
+    Some(video.parent()?.join(format!("{}.zoom.json", session_stem(video)?)))
+}
+
+/// Zoomed copy written next to a recording part
+pub fn zoomed_path(part: &Path) -> PathBuf {
+    let stem = part.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    part.with_file_name(format!("{}_zoom.mp4", stem))
+}
+
+/// Keyframes zooming in on every group of clicks and back out after it.
+///
+/// Clicks close enough that the view would not settle in between form one
+/// group, and the view pans from click to click within it.
+pub fn generate(telemetry: &Telemetry, settings: &AutoZoom) -> Vec<ZoomKeyframe> {
+    let area = &telemetry.area;
+    let clicks: Vec<(u64, (f64, f64))> = telemetry
+        .cursor
+        .iter()
+        .filter(|e| matches!(e.kind, CursorEventKind::Down { .. }))
+        .map(|e| {
+            let x = (e.x - area.x) as f64 / area.width.max(1) as f64;
+            let y = (e.y - area.y) as f64 / area.height.max(1) as f64;
+            (e.t, (x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)))
+        })
+        .collect();
+
+    let scale = settings.scale.clamp(1.0, MAX_ZOOM);
+    let transition = settings.transition_ms.max(1);
+    let gap = settings.hold_ms + 2 * transition;
+
+    let mut keyframes = Vec::new();
+    let mut i = 0;
+    while i < clicks.len() {
+        let mut j = i;
+        while j + 1 < clicks.len() && clicks[j + 1].0 - clicks[j].0 < gap {
+            j += 1;
+        }
+
+        let (first, center) = clicks[i];
+        keyframes.push(ZoomKeyframe::auto(first.saturating_sub(transition), 1.0, center));
+        keyframes.push(ZoomKeyframe::auto(first, scale, center));
+
+        for pair in clicks[i..=j].windows(2) {
+            let ((previous, from), (t, to)) = (pair[0], pair[1]);
+            // Hold on the previous click, then pan to arrive with the next one
+            let pan_start = t.saturating_sub(transition).max(previous);
+            if pan_start > previous {
+                keyframes.push(ZoomKeyframe::auto(pan_start, scale, from));
+            }
+            keyframes.push(ZoomKeyframe::auto(t, scale, to));
+        }
+
+        let (last, center) = clicks[j];
+        keyframes.push(ZoomKeyframe::auto(last + settings.hold_ms, scale, center));
+        keyframes.push(ZoomKeyframe::auto(last + settings.hold_ms + transition, 1.0, center));
+        i = j + 1;
+    }
+
+    keyframes
+}
+
+/// Combine generated keyframes with manual ones, which replace any
+/// generated keyframe within a transition of them
+pub fn timeline(generated: Vec<ZoomKeyframe>, manual: &[ZoomKeyframe], settings: &AutoZoom) -> Vec<ZoomKeyframe> {
+    let mut keyframes: Vec<ZoomKeyframe> = generated
+        .into_iter()
+        .filter(|k| manual.iter().all(|m| m.t.abs_diff(k.t) >= settings.transition_ms))
+        .chain(manual.iter().map(|m| ZoomKeyframe { manual: true, ..*m }))
+        .collect();
+    keyframes.sort_by_key(|k| k.t);
+    keyframes
+}
+
+/// FFmpeg expression of `value` over the input time `it`, easing between
+/// keyframes with a cosine curve.
+///
+/// Transitions outside the part starting at `offset` are folded into the
+/// base value so long recordings keep short expressions.
+fn expression(keyframes: &[ZoomKeyframe], value: impl Fn(&ZoomKeyframe) -> f64, offset: u64, duration: u64) -> String {
+    let Some(first) = keyframes.first() else {
+        return String::from("0");
+    };
+
+    let mut base = value(first);
+    let mut terms = String::new();
+    for pair in keyframes.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let delta = value(b) - value(a);
+        if delta.abs() < 1e-4 {
+            continue;
+        }
+        if b.t <= offset {
+            base += delta;
+            continue;
+        }
+        if a.t >= offset + duration {
+            break;
+        }
+
+        let start = (a.t as f64 - offset as f64) / 1000.0;
+        let length = (b.t - a.t) as f64 / 1000.0;
+        if length > 0.0 {
+            terms.push_str(&format!("{:+.4}*(1-cos(PI*clip((it{:+.3})/{:.3},0,1)))/2", delta, -start, length));
+        } else {
+            terms.push_str(&format!("{:+.4}*gte(it,{:.3})", delta, start));
+        }
+    }
+
+    format!("{:.4}{}", base, terms)
+}
+
+/// `zoompan` filter following `keyframes` for the part starting at `offset`
+pub fn zoompan_filter(keyframes: &[ZoomKeyframe], offset: u64, duration: u64, width: u32, height: u32, fps: f64) -> String {
+    let zoom = expression(keyframes, |k| k.zoom.clamp(1.0, MAX_ZOOM), offset, duration);
+    let x = expression(keyframes, |k| k.x.clamp(0.0, 1.0), offset, duration);
+    let y = expression(keyframes, |k| k.y.clamp(0.0, 1.0), offset, duration);
+
+    // `zoom` starts at 1 when no keyframes exist, which leaves the frame as is
+    format!(
+        "zoompan=z='max(1,{zoom})':x='clip(({x})*iw-iw/zoom/2,0,iw-iw/zoom)':y='clip(({y})*ih-ih/zoom/2,0,ih-ih/zoom)':d=1:s={width}x{height}:fps={fps}"
+    )
+}
+
+/// Render `source` zoomed along `keyframes` to `output`, returning the
+/// duration of `source` in milliseconds
+pub async fn render(
+    app_handle: &AppHandle,
+    source: &Path,
+    output: &Path,
+    keyframes: &[ZoomKeyframe],
+    offset: u64,
+    encoder: &EncoderProfile,
+) -> Result<u64> {
+    let info = probe::probe(app_handle, source).await?;
+    if info.width == 0 || info.height == 0 {
+        return Err(anyhow!("Could not read the video size of {}", source.display()));
+    }
+    // zoompan emits frames at its own rate, so it has to match the input
+    if info.frame_rate <= 0.0 {
+        return Err(anyhow!("Could not read the frame rate of {}", source.display()));
+    }
+
+    let duration = (info.duration * 1000.0) as u64;
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-i".into(),
+        source.to_string_lossy().to_string(),
+        "-map".into(),
+        "0".into(),
+        "-vf".into(),
+        zoompan_filter(keyframes, offset, duration, info.width, info.height, info.frame_rate),
+    ];
+    args.extend(encoder.video_codec_args());
+    args.extend([
+        "-pix_fmt".into(),
+        "yuv420p".into(),
+        "-c:a".into(),
+        "copy".into(),
+        "-movflags".into(),
+        "+faststart".into(),
+        "-f".into(),
+        "mp4".into(),
+        "-y".into(),
+        output.to_string_lossy().to_string(),
+    ]);
+
+    run_ffmpeg(app_handle, &args).await?;
+    Ok(duration)
+}
+
+/// Render a zoomed copy of every part of a recording, returning the copies.
+///
+/// Copies are written next to the parts, so the original stays untouched and
+/// can be zoomed again after editing the keyframes.
+pub async fn render_recording(
+    app_handle: &AppHandle,
+    parts: &[PathBuf],
+    keyframes: &[ZoomKeyframe],
+    encoder: &EncoderProfile,
+) -> Result<Vec<PathBuf>> {
+    let mut offset = 0;
+    let mut outputs = Vec::new();
+
+    for part in parts {
+        let output = zoomed_path(part);
+        let temp = output.with_extension("zoom.tmp");
+
+        let result = render(app_handle, part, &temp, keyframes, offset, encoder)
+            .await
+            .and_then(|duration| {
+                std::fs::rename(&temp, &output).context("Failed to save the zoomed recording")?;
+                Ok(duration)
+            });
+        match result {
+            Ok(duration) => offset += duration,
+            Err(e) => {
+                let _ = std::fs::remove_file(&temp);
+                return Err(e);
+            }
+        }
+        outputs.push(output);
+    }
+
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_points_replace_generated_ones_nearby() {
+        let generated = vec![
+            ZoomKeyframe::auto(0, 1.0, (0.5, 0.5)),
+            ZoomKeyframe::auto(1000, 2.0, (0.2, 0.3)),
+            ZoomKeyframe::auto(2000, 1.0, (0.5, 0.5)),
+        ];
+        let manual = [ZoomKeyframe::auto(1300, 3.0, (0.8, 0.8))];
+        let keyframes = timeline(generated, &manual, &AutoZoom::default());
+
+        let times: Vec<(u64, bool)> = keyframes.iter().map(|k| (k.t, k.manual)).collect();
+        assert_eq!(times, [(0, false), (1300, true), (2000, false)]);
+        assert_eq!(keyframes[1].zoom, 3.0);
+    }
+
+    #[test]
+    fn timeline_without_manual_points_is_the_generated_one() {
+        let generated = vec![ZoomKeyframe::auto(500, 2.0, (0.1, 0.9)), ZoomKeyframe::auto(0, 1.0, (0.5, 0.5))];
+        let keyframes = timeline(generated, &[], &AutoZoom::default());
+        assert_eq!(keyframes.iter().map(|k| k.t).collect::<Vec<_>>(), [0, 500]);
+        assert!(keyframes.iter().all(|k| !k.manual));
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+
+use crate::desktop::notify;
+use crate::error::AppError;
+use crate::desktop::sound::{self, Cue};
+
+use super::ffmpeg::FFmpegRecorder;
+use super::state::{RecordingState, RecordingStatus};
+
+/// How often the monitor samples the output file and the free space
+const MONITOR_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Bytes in a megabyte, as used by the size settings
+pub const MB: u64 = 1024 * 1024;
+
+/// Payload of the `disk-space-warning` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskSpaceWarning {
+    pub path: String,
+    pub available_bytes: u64,
+    pub file_size: u64,
+}
+
+/// Payload of the `recording-auto-stopped` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingAutoStopped {
+    pub reason: String,
+    pub path: Option<String>,
+    pub available_bytes: u64,
+}
+
+/// Payload of the `recording-part-started` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingPartStarted {
+    pub part: u32,
+    pub path: String,
+}
+
+/// Get free space available to the current user on the volume holding `dir`
+pub fn available_space(dir: &Path) -> Result<u64> {
+    fs2::available_space(dir)
+        .map_err(|e| anyhow!("Failed to query free space for {}: {}", dir.display(), e))
+}
+
+/// Fail if the volume holding `dir` has less than `required` bytes free
+pub fn ensure_free_space(dir: &Path, required: u64) -> Result<()> {
+    let available = available_space(dir)?;
+    if available < required {
+        return Err(AppError::DiskFull {
+            path: dir.to_path_buf(),
+            available_mb: available / MB,
+            required_mb: required / MB,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Watch the output of recording session `session_id` until it ends.
+///
+/// Emits a warning once free space drops below the warning threshold, reports
+/// new parts, rolls over to the next part when the max file size is reached,
+/// and stops the recording gracefully before the disk fills up.
+///
+/// The size is only sampled every `MONITOR_INTERVAL`, so a part grows past
+/// the max file size by up to that much video before the next one starts.
+/// The segment muxer can only split by time, and `-fs` would end the
+/// recording instead of starting a new part, so rolling over stops FFmpeg
+/// gracefully and starts a new one; the capture pauses while it restarts.
+pub fn spawn_monitor(app_handle: AppHandle, session_id: u64) {
+    tauri::async_runtime::spawn(async move {
+        let mut warned = false;
+
+        loop {
+            tokio::time::sleep(MONITOR_INTERVAL).await;
+
+            let recorder = app_handle.state::<Arc<Mutex<FFmpegRecorder>>>();
+            let mut recorder = recorder.lock().await;
+
+            if recorder.session_id() != session_id || recorder.output_path().is_none() {
+                break;
+            }
+
+            let Some(options) = recorder.options().cloned() else {
+                break;
+            };
+
+            for (part, next) in recorder.sync_parts() {
+                let _ = app_handle.emit(
+                    "recording-part-started",
+                    RecordingPartStarted {
+                        part,
+                        path: next.to_string_lossy().to_string(),
+                    },
+                );
+            }
+
+            let path = recorder.output_path().cloned().unwrap_or_default();
+            let file_size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+            let available = match available_space(&options.output_dir) {
+                Ok(available) => available,
+                Err(e) => {
+                    tracing::warn!("Disk monitor: {}", e);
+                    continue;
+                }
+            };
+
+            // Stop before the disk fills up so FFmpeg can finalize the file
+            if available <= options.min_free_space {
+                let output = recorder.stop().await.ok().flatten();
+
+                let state = app_handle.state::<Arc<Mutex<RecordingState>>>();
+                let mut state = state.lock().await;
+                state.status = RecordingStatus::Idle;
+                state.start_time = None;
+
+                sound::play_cue(&app_handle, Cue::Error).await;
+                notify::error(
+                    &app_handle,
+                    "Recording stopped",
+                    "The disk is almost full. The recording so far has been saved.",
+                )
+                .await;
+                let _ = app_handle.emit(
+                    "recording-auto-stopped",
+                    RecordingAutoStopped {
+                        reason: String::from("diskFull"),
+                        path: output.map(|p| p.to_string_lossy().to_string()),
+                        available_bytes: available,
+                    },
+                );
+                break;
+            }
+
+            if available <= options.low_disk_warning {
+                if !warned {
+                    warned = true;
+                    let _ = app_handle.emit(
+                        "disk-space-warning",
+                        DiskSpaceWarning {
+                            path: path.to_string_lossy().to_string(),
+                            available_bytes: available,
+                            file_size,
+                        },
+                    );
+                }
+            } else {
+                warned = false;
+            }
+
+            if let Some(max_file_size) = options.max_file_size {
+                if file_size >= max_file_size {
+                    match recorder.next_part().await {
+                        Ok(next) => {
+                            let _ = app_handle.emit(
+                                "recording-part-started",
+                                RecordingPartStarted {
+                                    part: recorder.part(),
+                                    path: next.to_string_lossy().to_string(),
+                                },
+                            );
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to start next recording part: {}", e);
+
+                            let state = app_handle.state::<Arc<Mutex<RecordingState>>>();
+                            state.lock().await.reset();
+
+                            sound::play_cue(&app_handle, Cue::Error).await;
+                            notify::error(
+                                &app_handle,
+                                "Recording stopped",
+                                &format!("Failed to start the next part: {}", e),
+                            )
+                            .await;
+                            let _ = app_handle.emit(
+                                "recording-auto-stopped",
+                                RecordingAutoStopped {
+                                    reason: String::from("partFailed"),
+                                    path: Some(path.to_string_lossy().to_string()),
+                                    available_bytes: available,
+                                },
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
@@ -1,26 +1,251 @@
 use anyhow::{anyhow, Result};
+use serde::Serialize;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::AppHandle;
-use tauri_plugin_shell::{ShellExt, process::CommandChild};
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::{ShellExt, process::{CommandChild, CommandEvent}};
+use tokio::sync::{oneshot, Mutex};
+
+use super::disk;
+use crate::desktop::notify;
+use crate::error::AppError;
+use crate::logging::SessionLog;
+use crate::desktop::sound::{self, Cue};
+use crate::media::audio::{MIC_TRACK, MIX_TRACK, SYSTEM_TRACK};
+use crate::media::keystrokes::KeystrokeFilter;
+use crate::media::profile::EncoderProfile;
+use super::filename::{self, TemplateContext};
+use super::state::{RecordingMode, RecordingSources, RecordingState, RecordingStatus, Region};
+use super::input::ListenOptions;
+use super::telemetry::{self, CaptureArea, TelemetryRecorder};
+
+/// FFmpeg input device capturing the screen
+pub const SCREEN_DEVICE: &str = "gdigrab";
+/// FFmpeg input device capturing audio
+pub const AUDIO_DEVICE: &str = "dshow";
+
+/// How long FFmpeg gets to finalize a part after `q`. Moving the index to
+/// the front for `+faststart` rewrites the whole file.
+const FINISH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Payload of the `recording-failed` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingFailed {
+    /// Part that was being written, possibly unplayable
+    pub path: Option<String>,
+    pub error: String,
+}
+
+/// Wait for the FFmpeg process `pid` to exit. If it exits while it is still
+/// the recorder's process, nobody asked it to, so the session is ended and
+/// the failure reported.
+///
+/// FFmpeg's output and exit code go to the session log either way, and
+/// `exited` fires once it is gone.
+async fn watch_process(
+    app_handle: AppHandle,
+    mut rx: Receiver<CommandEvent>,
+    pid: u32,
+    log: SessionLog,
+    exited: oneshot::Sender<()>,
+) {
+    let mut stderr = String::new();
+    let mut code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stderr(line) => {
+                let line = String::from_utf8_lossy(&line);
+                log.stderr(&line);
+                stderr.push_str(&line);
+                // Warnings repeat for as long as frames drop, only the end is reported
+                if stderr.len() > 64 * 1024 {
+                    let start = stderr.len() - 16 * 1024;
+                    let start = (start..stderr.len()).find(|i| stderr.is_char_boundary(*i)).unwrap_or(stderr.len());
+                    stderr.drain(..start);
+                }
+            }
+            CommandEvent::Terminated(payload) => {
+                code = payload.code;
+                log.exited(code);
+                break;
+            }
+            _ => {}
+        }
+    }
+    // Before locking, the recorder may be waiting for this with the lock held
+    let _ = exited.send(());
+
+    let recorder = app_handle.state::<Arc<Mutex<FFmpegRecorder>>>();
+    let mut recorder = recorder.lock().await;
+    if recorder.process.as_ref().map(|p| p.pid()) != Some(pid) {
+        return;
+    }
 
-use super::state::{RecordingMode, RecordingSources, Region};
+    recorder.process = None;
+    recorder.finish_telemetry();
+    let path = recorder.output_path.take();
+    recorder.session = None;
+    recorder.is_paused = false;
+
+    let state = app_handle.state::<Arc<Mutex<RecordingState>>>();
+    let mut state = state.lock().await;
+    state.status = RecordingStatus::Idle;
+    state.start_time = None;
+    drop(state);
+    drop(recorder);
+
+    let tail = crate::media::stderr_tail(&stderr);
+    let error = match (code, tail.is_empty()) {
+        (_, false) => format!("FFmpeg stopped unexpectedly: {}", tail),
+        (Some(code), true) => format!("FFmpeg stopped unexpectedly with exit code {}", code),
+        (None, true) => String::from("FFmpeg stopped unexpectedly"),
+    };
+    tracing::error!("{}", error);
+
+    sound::play_cue(&app_handle, Cue::Error).await;
+    notify::error(&app_handle, "Recording failed", &error).await;
+    let _ = app_handle.emit(
+        "recording-failed",
+        RecordingFailed {
+            path: path.map(|p| p.to_string_lossy().to_string()),
+            error,
+        },
+    );
+}
+
+/// Output options resolved from settings for one recording session
+#[derive(Debug, Clone)]
+pub struct RecordingOptions {
+    pub output_dir: PathBuf,
+    pub resolution: String,
+    pub frame_rate: u32,
+    /// Roll over to a new numbered part once a part reaches this size (bytes).
+    /// Checked by the disk monitor, so parts overshoot it a little, and
+    /// the next part starts once FFmpeg has finalized the previous one.
+    pub max_file_size: Option<u64>,
+    /// Split into parts of this many seconds using the segment muxer
+    pub split_duration: Option<u32>,
+    /// Output filename template, see `filename::render`
+    pub filename_template: String,
+    /// Video and audio encoder settings
+    pub encoder: EncoderProfile,
+    /// Live processing chain for system audio, see `AudioProcessing`
+    pub system_audio_filter: Option<String>,
+    /// Live processing chain for the microphone
+    pub microphone_filter: Option<String>,
+    /// Record pointer telemetry and leave the cursor out of the video, so
+    /// cursor effects can be composited afterwards
+    pub cursor_telemetry: bool,
+    /// Record key presses through this filter for the keystroke overlay
+    pub keystrokes: Option<KeystrokeFilter>,
+    /// Warn once free space drops below this many bytes
+    pub low_disk_warning: u64,
+    /// Refuse to start, and stop gracefully, below this many free bytes
+    pub min_free_space: u64,
+}
+
+/// Capture parameters kept for the whole session so new parts can be spawned
+#[derive(Debug, Clone)]
+struct RecordingSession {
+    mode: RecordingMode,
+    region: Option<Region>,
+    sources: RecordingSources,
+    options: RecordingOptions,
+    /// Folder the parts are written to, below the output directory
+    dir: PathBuf,
+    /// Filename without extension, e.g. `FlashScreen_20240101_120000`
+    stem: String,
+    /// Capture the system cursor into the video
+    draw_mouse: bool,
+    /// FFmpeg command lines and output of all parts
+    log: SessionLog,
+}
+
+impl RecordingOptions {
+    /// Whether recordings are written as numbered parts
+    fn is_split(&self) -> bool {
+        self.max_file_size.is_some() || self.split_duration.is_some()
+    }
+}
+
+/// Title of the window `window_id` (an `HWND`, in decimal or `0x` hex) for
+/// the `{window_title}` token, `None` when it can't be resolved
+#[cfg(windows)]
+fn window_title(window_id: &str) -> Option<String> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetWindowTextLengthW, GetWindowTextW, IsWindow};
+
+    let handle = match window_id.strip_prefix("0x") {
+        Some(hex) => isize::from_str_radix(hex, 16).ok()?,
+        None => window_id.parse().ok()?,
+    } as windows_sys::Win32::Foundation::HWND;
+
+    unsafe {
+        if IsWindow(handle) == 0 {
+            return None;
+        }
+        let mut buffer = vec![0u16; GetWindowTextLengthW(handle) as usize + 1];
+        let len = GetWindowTextW(handle, buffer.as_mut_ptr(), buffer.len() as i32);
+        let title = String::from_utf16_lossy(&buffer[..len.max(0) as usize]);
+        (!title.trim().is_empty()).then_some(title)
+    }
+}
+
+#[cfg(not(windows))]
+fn window_title(_window_id: &str) -> Option<String> {
+    None
+}
+
+/// File name of a part; parts are only numbered when splitting is enabled
+fn part_filename(stem: &str, part: u32, split: bool) -> String {
+    if split {
+        format!("{}_part{:03}.mp4", stem, part)
+    } else {
+        format!("{}.mp4", stem)
+    }
+}
+
+impl RecordingSession {
+    /// Output path of a part
+    fn part_path(&self, part: u32) -> PathBuf {
+        self.dir.join(part_filename(&self.stem, part, self.options.is_split()))
+    }
+
+    /// Output pattern handed to the segment muxer
+    fn part_pattern(&self) -> PathBuf {
+        self.dir.join(format!("{}_part%03d.mp4", self.stem))
+    }
+}
 
 /// FFmpeg recorder for screen capture using embedded sidecar
 pub struct FFmpegRecorder {
     process: Option<CommandChild>,
+    /// Fires when `process` has exited
+    exited: Option<oneshot::Receiver<()>>,
     output_path: Option<PathBuf>,
     is_paused: bool,
     app_handle: Option<Arc<AppHandle>>,
+    session: Option<RecordingSession>,
+    session_id: u64,
+    part: u32,
+    parts: Vec<PathBuf>,
+    telemetry: Option<TelemetryRecorder>,
 }
 
 impl FFmpegRecorder {
     pub fn new() -> Self {
         Self {
             process: None,
+            exited: None,
             output_path: None,
             is_paused: false,
             app_handle: None,
+            session: None,
+            session_id: 0,
+            part: 0,
+            parts: Vec::new(),
+            telemetry: None,
         }
     }
 
@@ -29,26 +254,28 @@ impl FFmpegRecorder {
         self.app_handle = Some(handle);
     }
 
-    /// Build FFmpeg arguments for screen recording
-    fn build_args(
-        &self,
-        mode: RecordingMode,
-        region: Option<&Region>,
-        sources: &RecordingSources,
-        output_path: &PathBuf,
-        resolution: &str,
-        frame_rate: u32,
-    ) -> Vec<String> {
+    /// Build FFmpeg arguments for screen recording, starting at part `part`
+    fn build_args(&self, session: &RecordingSession, part: u32) -> Vec<String> {
+        let mode = session.mode;
+        let region = session.region.as_ref();
+        let sources = &session.sources;
+        let resolution = session.options.resolution.as_str();
+        let frame_rate = session.options.frame_rate;
+
         let mut args: Vec<String> = Vec::new();
 
         // Input: Screen capture using GDI grab (Windows)
         args.push("-f".to_string());
-        args.push("gdigrab".to_string());
+        args.push(SCREEN_DEVICE.to_string());
 
         // Frame rate
         args.push("-framerate".to_string());
         args.push(frame_rate.to_string());
 
+        // Cursor effects draw their own cursor from the telemetry
+        args.push("-draw_mouse".to_string());
+        args.push(if session.draw_mouse { "1" } else { "0" }.to_string());
+
         // Region settings
         match mode {
             RecordingMode::Fullscreen => {
@@ -79,7 +306,7 @@ impl FFmpegRecorder {
         // This captures desktop/system audio
         if sources.system_audio {
             args.push("-f".to_string());
-            args.push("dshow".to_string());
+            args.push(AUDIO_DEVICE.to_string());
             args.push("-i".to_string());
             args.push("audio=Stereo Mix".to_string());
         }
@@ -87,20 +314,13 @@ impl FFmpegRecorder {
         // Audio input: Default microphone
         if sources.microphone {
             args.push("-f".to_string());
-            args.push("dshow".to_string());
+            args.push(AUDIO_DEVICE.to_string());
             args.push("-i".to_string());
             args.push("audio=Microphone".to_string());
         }
 
         // Video codec settings
-        args.push("-c:v".to_string());
-        args.push("libx264".to_string());
-        args.push("-preset".to_string());
-        args.push("ultrafast".to_string()); // Fast encoding for real-time
-        args.push("-tune".to_string());
-        args.push("zerolatency".to_string());
-        args.push("-crf".to_string());
-        args.push("23".to_string()); // Quality setting
+        args.extend(session.options.encoder.video_codec_args());
 
         // Resolution scaling if needed
         match resolution {
@@ -123,30 +343,99 @@ impl FFmpegRecorder {
             _ => {} // Original resolution
         }
 
-        // Audio codec
-        if sources.system_audio || sources.microphone {
-            args.push("-c:a".to_string());
-            args.push("aac".to_string());
-            args.push("-b:a".to_string());
-            args.push("128k".to_string());
+        // Audio tracks: every source on its own track so it can be extracted
+        // later, preceded by a mix of both for players that only play one
+        let audio_tracks: Vec<(&str, Option<&String>)> = [
+            (sources.system_audio, SYSTEM_TRACK, session.options.system_audio_filter.as_ref()),
+            (sources.microphone, MIC_TRACK, session.options.microphone_filter.as_ref()),
+        ]
+        .into_iter()
+        .filter(|(enabled, _, _)| *enabled)
+        .map(|(_, title, filter)| (title, filter))
+        .collect();
+
+        if !audio_tracks.is_empty() {
+            let mix = audio_tracks.len() > 1;
+            let mut graph = Vec::new();
+            let mut maps = Vec::new();
+            let mut mix_inputs = String::new();
+
+            for (i, (title, filter)) in audio_tracks.iter().enumerate() {
+                let input = i + 1;
+                match (filter, mix) {
+                    (Some(filter), true) => {
+                        graph.push(format!("[{input}:a]{filter},asplit=2[s{input}][m{input}]"));
+                        maps.push((format!("[s{input}]"), *title));
+                        mix_inputs.push_str(&format!("[m{input}]"));
+                    }
+                    (Some(filter), false) => {
+                        graph.push(format!("[{input}:a]{filter}[s{input}]"));
+                        maps.push((format!("[s{input}]"), *title));
+                    }
+                    (None, _) => {
+                        maps.push((format!("{input}:a"), *title));
+                        mix_inputs.push_str(&format!("[{input}:a]"));
+                    }
+                }
+            }
+            if mix {
+                graph.push(format!("{}amix=inputs={}:normalize=0[mix]", mix_inputs, audio_tracks.len()));
+                maps.insert(0, (String::from("[mix]"), MIX_TRACK));
+            }
+
+            if !graph.is_empty() {
+                args.push("-filter_complex".to_string());
+                args.push(graph.join(";"));
+            }
+            args.push("-map".to_string());
+            args.push("0:v".to_string());
+            for (i, (map, title)) in maps.iter().enumerate() {
+                args.push("-map".to_string());
+                args.push(map.clone());
+                args.push(format!("-metadata:s:a:{}", i));
+                args.push(format!("handler_name={}", title));
+            }
+
+            // Audio codec
+            args.extend(session.options.encoder.audio_codec_args());
         }
 
         // Output format
-        args.push("-f".to_string());
-        args.push("mp4".to_string());
-        args.push("-movflags".to_string());
-        args.push("+faststart".to_string());
+        let output_path = if let Some(segment_time) = session.options.split_duration {
+            // Segment muxer: a new part every `segment_time` seconds, each starting at t=0
+            args.push("-f".to_string());
+            args.push("segment".to_string());
+            args.push("-segment_time".to_string());
+            args.push(segment_time.to_string());
+            args.push("-reset_timestamps".to_string());
+            args.push("1".to_string());
+            args.push("-segment_start_number".to_string());
+            args.push(part.to_string());
+            args.push("-segment_format".to_string());
+            args.push("mp4".to_string());
+            args.push("-segment_format_options".to_string());
+            args.push("movflags=+faststart".to_string());
+
+            session.part_pattern()
+        } else {
+            args.push("-f".to_string());
+            args.push("mp4".to_string());
+            args.push("-movflags".to_string());
+            args.push("+faststart".to_string());
 
-        // Overwrite output file if exists
-        args.push("-y".to_string());
+            session.part_path(part)
+        };
+
+        // Never overwrite, the filename is already checked for collisions
+        args.push("-n".to_string());
 
         // Output path
         args.push(output_path.to_string_lossy().to_string());
 
-        // Suppress banner
+        // Suppress banner; warnings such as dropped frames go to the session log
         args.push("-hide_banner".to_string());
         args.push("-loglevel".to_string());
-        args.push("error".to_string());
+        args.push("warning".to_string());
 
         args
     }
@@ -156,77 +445,239 @@ impl FFmpegRecorder {
         &mut self,
         mode: RecordingMode,
         region: Option<Region>,
+        window_id: Option<&str>,
         sources: RecordingSources,
-        output_dir: &PathBuf,
-        resolution: &str,
-        frame_rate: u32,
+        options: RecordingOptions,
     ) -> Result<PathBuf> {
         if self.process.is_some() {
-            return Err(anyhow!("Recording already in progress"));
+            return Err(AppError::AlreadyRecording.into());
         }
 
-        let app_handle = self.app_handle.as_ref()
-            .ok_or_else(|| anyhow!("App handle not set"))?;
+        // Ensure output directory exists
+        if !options.output_dir.exists() {
+            std::fs::create_dir_all(&options.output_dir)?;
+        }
 
-        // Generate output filename
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let filename = format!("FlashScreen_{}.mp4", timestamp);
-        let output_path = output_dir.join(&filename);
+        // Pre-flight check so we don't start a recording that can't be finalized
+        disk::ensure_free_space(&options.output_dir, options.min_free_space)?;
 
-        // Ensure output directory exists
-        if !output_dir.exists() {
-            std::fs::create_dir_all(output_dir)?;
+        // Generate output filename from the template
+        let window_title = window_id.and_then(window_title);
+        let ctx = TemplateContext {
+            now: chrono::Local::now(),
+            mode,
+            region: region.as_ref(),
+            window_title: window_title.as_deref(),
+            display: "desktop",
+            resolution: &options.resolution,
+        };
+        let split = options.is_split();
+        let (dir, stem) = filename::resolve(
+            &options.output_dir,
+            &options.filename_template,
+            &ctx,
+            |dir, stem| {
+                dir.join(part_filename(stem, 1, false)).exists()
+                    || (split && dir.join(part_filename(stem, 1, true)).exists())
+            },
+        )?;
+
+        // Subfolders from the template
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)?;
         }
 
-        // Build FFmpeg arguments
-        let args = self.build_args(
+        // Fall back to the system cursor where input can't be recorded
+        if let Some(previous) = self.telemetry.take() {
+            previous.discard();
+        }
+        let listen = ListenOptions {
+            cursor: options.cursor_telemetry,
+            keys: options.keystrokes,
+        };
+        let telemetry = if listen.cursor || listen.keys.is_some() {
+            let area = region.as_ref().map(|r| CaptureArea {
+                x: r.x,
+                y: r.y,
+                width: r.width,
+                height: r.height,
+            });
+            TelemetryRecorder::start(telemetry::telemetry_path(&dir, &stem), area, listen)
+                .await
+                .map_err(|e| tracing::warn!("Input telemetry unavailable: {}", e))
+                .ok()
+        } else {
+            None
+        };
+
+        let log = SessionLog::new(&stem);
+        let session = RecordingSession {
             mode,
-            region.as_ref(),
-            &sources,
-            &output_path,
-            resolution,
-            frame_rate,
-        );
+            region,
+            sources,
+            options,
+            dir,
+            stem,
+            draw_mouse: telemetry.is_none() || !listen.cursor,
+            log,
+        };
+
+        let output_path = match self.spawn(&session, 1) {
+            Ok(output_path) => output_path,
+            Err(e) => {
+                if let Some(telemetry) = telemetry {
+                    telemetry.discard();
+                }
+                return Err(e);
+            }
+        };
+        self.telemetry = telemetry;
+
+        self.session = Some(session);
+        self.session_id += 1;
+        self.part = 1;
+        self.parts = vec![output_path.clone()];
+        self.is_paused = false;
+
+        Ok(output_path)
+    }
+
+    /// Finish the current part and continue recording into the next numbered part
+    pub async fn next_part(&mut self) -> Result<PathBuf> {
+        let session = self.session.clone()
+            .ok_or(AppError::NotRecording)?;
+
+        self.finish().await;
+
+        let output_path = match self.spawn(&session, self.part + 1) {
+            Ok(output_path) => output_path,
+            Err(e) => {
+                self.session = None;
+                self.output_path = None;
+                self.finish_telemetry();
+                return Err(e);
+            }
+        };
+
+        self.part += 1;
+        self.parts.push(output_path.clone());
+
+        Ok(output_path)
+    }
+
+    /// Spawn the FFmpeg sidecar writing part `part`, returning its path
+    fn spawn(&mut self, session: &RecordingSession, part: u32) -> Result<PathBuf> {
+        let app_handle = self.app_handle.as_ref()
+            .ok_or_else(|| anyhow!("App handle not set"))?;
+
+        // Build FFmpeg arguments
+        let args = self.build_args(session, part);
+        session.log.command(part, &args);
 
         // Use sidecar (embedded FFmpeg)
         let shell = app_handle.shell();
         let sidecar = shell.sidecar("ffmpeg")
-            .map_err(|e| anyhow!("Failed to create sidecar: {}", e))?
+            .map_err(|e| AppError::FfmpegMissing { message: e.to_string() })?
             .args(&args);
 
-        let (mut _rx, child) = sidecar.spawn()
-            .map_err(|e| anyhow!("Failed to spawn FFmpeg: {}", e))?;
-
+        let (rx, child) = sidecar.spawn()
+            .map_err(|e| AppError::FfmpegMissing { message: e.to_string() })?;
+        let (exited_tx, exited_rx) = oneshot::channel();
+        tauri::async_runtime::spawn(watch_process(
+            app_handle.as_ref().clone(),
+            rx,
+            child.pid(),
+            session.log.clone(),
+            exited_tx,
+        ));
+
+        let output_path = session.part_path(part);
         self.process = Some(child);
+        self.exited = Some(exited_rx);
         self.output_path = Some(output_path.clone());
-        self.is_paused = false;
 
         Ok(output_path)
     }
 
-    /// Stop recording
-    pub async fn stop(&mut self) -> Result<Option<PathBuf>> {
-        if let Some(process) = self.process.take() {
+    /// Pick up parts the segment muxer started on its own since the last call
+    pub fn sync_parts(&mut self) -> Vec<(u32, PathBuf)> {
+        let mut started = Vec::new();
+
+        if let Some(session) = self.session.as_ref().filter(|s| s.options.split_duration.is_some()) {
+            loop {
+                let next = session.part_path(self.part + 1);
+                if !next.exists() {
+                    break;
+                }
+                self.part += 1;
+                self.parts.push(next.clone());
+                self.output_path = Some(next.clone());
+                started.push((self.part, next));
+            }
+        }
+
+        started
+    }
+
+    /// Ask FFmpeg to finalize the current part and wait for it to exit
+    async fn finish(&mut self) {
+        if let Some(mut process) = self.process.take() {
             // Send 'q' to FFmpeg stdin to gracefully stop
             let _ = process.write(b"q");
-            
-            // Give FFmpeg time to finish writing
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            
-            // Kill if still running
-            let _ = process.kill();
 
-            let output = self.output_path.take();
-            self.is_paused = false;
+            // Kill it only if it doesn't get done
+            if !self.wait_for_exit(FINISH_TIMEOUT).await {
+                tracing::warn!("FFmpeg did not finish the recording in time, killing it");
+                let _ = process.kill();
+                self.wait_for_exit(std::time::Duration::from_secs(5)).await;
+            }
+        }
+    }
 
-            Ok(output)
-        } else {
-            Ok(None)
+    /// Wait up to `timeout` for the process to exit, returning whether it did
+    async fn wait_for_exit(&mut self, timeout: std::time::Duration) -> bool {
+        let Some(exited) = self.exited.as_mut() else {
+            return true;
+        };
+        match tokio::time::timeout(timeout, exited).await {
+            Ok(_) => {
+                self.exited = None;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Stop recording
+    pub async fn stop(&mut self) -> Result<Option<PathBuf>> {
+        if self.process.is_none() {
+            return Ok(None);
+        }
+
+        self.finish().await;
+        self.finish_telemetry();
+
+        let output = self.output_path.take();
+        self.session = None;
+        self.is_paused = false;
+
+        Ok(output)
+    }
+
+    /// Write the telemetry of the session next to its recording
+    fn finish_telemetry(&mut self) {
+        if let Some(telemetry) = self.telemetry.take() {
+            if let Err(e) = telemetry.finish() {
+                tracing::warn!("Failed to save cursor telemetry: {}", e);
+            }
         }
     }
 
     /// Pause recording (Windows: not directly supported, we'll stop and restart)
     pub async fn pause(&mut self) -> Result<()> {
+        if self.process.is_none() {
+            return Err(AppError::NotRecording.into());
+        }
         self.is_paused = true;
         // Note: FFmpeg doesn't support pause natively
         // For a proper implementation, we'd need to use segment recording
@@ -236,26 +687,36 @@ impl FFmpegRecorder {
 
     /// Resume recording
     pub async fn resume(&mut self) -> Result<()> {
+        if self.process.is_none() {
+            return Err(AppError::NotRecording.into());
+        }
         self.is_paused = false;
         Ok(())
     }
 
-    /// Cancel recording and delete output file
+    /// Cancel recording and delete output files
     pub async fn cancel(&mut self) -> Result<()> {
+        if let Some(telemetry) = self.telemetry.take() {
+            telemetry.discard();
+        }
+
         if let Some(process) = self.process.take() {
             // Kill the process immediately
             let _ = process.kill();
 
-            // Delete the incomplete file
-            if let Some(path) = self.output_path.take() {
-                // Give a moment for file handle to release
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            // Wait for it to release the files
+            self.wait_for_exit(std::time::Duration::from_secs(5)).await;
+
+            // Delete the incomplete files
+            for path in self.parts.drain(..) {
                 if path.exists() {
                     let _ = std::fs::remove_file(path);
                 }
             }
         }
 
+        self.output_path = None;
+        self.session = None;
         self.is_paused = false;
         Ok(())
     }
@@ -274,6 +735,21 @@ impl FFmpegRecorder {
     pub fn output_path(&self) -> Option<&PathBuf> {
         self.output_path.as_ref()
     }
+
+    /// Get the options of the current session
+    pub fn options(&self) -> Option<&RecordingOptions> {
+        self.session.as_ref().map(|s| &s.options)
+    }
+
+    /// Identifier of the current session, bumped on every start
+    pub fn session_id(&self) -> u64 {
+        self.session_id
+    }
+
+    /// Number of the part currently being written, starting at 1
+    pub fn part(&self) -> u32 {
+        self.part
+    }
 }
 
 impl Default for FFmpegRecorder {
@@ -321,18 +797,3 @@ pub async fn get_video_devices(app_handle: &AppHandle) -> Result<Vec<String>> {
 
     Ok(devices)
 }
-
-/// Check if FFmpeg sidecar is available
-pub async fn check_ffmpeg(app_handle: &AppHandle) -> Result<bool> {
-    let shell = app_handle.shell();
-    let result = shell.sidecar("ffmpeg")
-        .map_err(|e| anyhow!("Sidecar error: {}", e))?
-        .args(["-version"])
-        .output()
-        .await;
-
-    match result {
-        Ok(o) => Ok(o.status.success()),
-        Err(_) => Ok(false),
-    }
-}
@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+
+use super::state::{RecordingMode, Region};
+
+/// Template used when the user hasn't configured one
+pub const DEFAULT_TEMPLATE: &str = "FlashScreen_{date}_{time}";
+
+/// Characters that are not allowed in file names on any supported platform
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Values available to filename template tokens
+pub struct TemplateContext<'a> {
+    pub now: DateTime<Local>,
+    pub mode: RecordingMode,
+    pub region: Option<&'a Region>,
+    pub window_title: Option<&'a str>,
+    pub display: &'a str,
+    pub resolution: &'a str,
+}
+
+/// Whether `name` (with or without extension) is a reserved device name
+pub fn is_reserved_name(name: &str) -> bool {
+    let base = name.split('.').next().unwrap_or(name).trim();
+    RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(base))
+}
+
+/// Make a single path component safe to use as a file or folder name
+pub fn sanitize_component(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if ILLEGAL_CHARS.contains(&c) || c.is_control() { '_' } else { c })
+        .collect();
+
+    // Windows silently drops trailing dots and spaces
+    let cleaned = cleaned.trim().trim_end_matches(['.', ' ']).to_string();
+
+    if cleaned.is_empty() || cleaned == "." || cleaned == ".." {
+        return String::from("_");
+    }
+    if is_reserved_name(&cleaned) {
+        return format!("_{}", cleaned);
+    }
+    cleaned
+}
+
+/// Expand the tokens of one template component
+fn expand(component: &str, ctx: &TemplateContext, counter: u32) -> String {
+    let mode = match ctx.mode {
+        RecordingMode::Fullscreen => "fullscreen",
+        RecordingMode::Window => "window",
+        RecordingMode::Region => "region",
+    };
+
+    // Region recordings at original size are named after the region itself
+    let resolution = match (ctx.resolution, ctx.region) {
+        ("original", Some(r)) => format!("{}x{}", r.width, r.height),
+        (resolution, _) => resolution.to_string(),
+    };
+
+    let value = |token: &str| -> Option<String> {
+        Some(match token {
+            "date" => ctx.now.format("%Y%m%d").to_string(),
+            "time" => ctx.now.format("%H%M%S").to_string(),
+            "yyyy" => ctx.now.format("%Y").to_string(),
+            "mm" => ctx.now.format("%m").to_string(),
+            "dd" => ctx.now.format("%d").to_string(),
+            "mode" => mode.to_string(),
+            "window_title" => ctx.window_title.unwrap_or(mode).to_string(),
+            "display" => ctx.display.to_string(),
+            "resolution" => resolution.clone(),
+            "counter" => format!("{:03}", counter),
+            _ => return None,
+        })
+    };
+
+    // In one pass, so a window title containing a token stays as it is
+    let mut expanded = String::new();
+    let mut rest = component;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let token = &rest[start..];
+        match token.find('}').and_then(|end| Some((end, value(&token[1..end])?))) {
+            Some((end, value)) => {
+                expanded.push_str(&value);
+                rest = &token[end + 1..];
+            }
+            None => {
+                expanded.push('{');
+                rest = &token[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Render `template` into a sanitized relative folder and a file stem.
+///
+/// Every `/` in the template starts a subfolder, e.g. `{yyyy}/{mm}/{time}`.
+pub fn render(template: &str, ctx: &TemplateContext, counter: u32) -> Result<(PathBuf, String)> {
+    let template = if template.trim().is_empty() { DEFAULT_TEMPLATE } else { template };
+
+    let mut components: Vec<String> = template
+        .split(['/', '\\'])
+        .filter(|c| !c.trim().is_empty())
+        .map(|c| sanitize_component(&expand(c, ctx, counter)))
+        .collect();
+
+    let stem = components
+        .pop()
+        .ok_or_else(|| anyhow!("Filename template '{}' is empty", template))?;
+
+    Ok((components.iter().collect(), stem))
+}
+
+/// Resolve a template to a folder and stem that don't clash with existing files.
+///
+/// Templates containing `{counter}` count up from 1; otherwise `_2`, `_3`, ...
+/// is appended on collision. `is_taken` checks the stem against every file the
+/// recording would write, such as numbered parts.
+pub fn resolve(
+    output_dir: &Path,
+    template: &str,
+    ctx: &TemplateContext,
+    is_taken: impl Fn(&Path, &str) -> bool,
+) -> Result<(PathBuf, String)> {
+    let has_counter = template.contains("{counter}");
+
+    for n in 1..10_000 {
+        let (subdir, stem) = render(template, ctx, if has_counter { n } else { 1 })?;
+        let dir = output_dir.join(subdir);
+
+        let stem = if has_counter || n == 1 { stem } else { format!("{}_{}", stem, n) };
+        if !is_taken(&dir, &stem) {
+            return Ok((dir, stem));
+        }
+    }
+
+    Err(anyhow!("Could not find a free filename for template '{}'", template))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn context(region: Option<&Region>) -> TemplateContext<'_> {
+        TemplateContext {
+            now: Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 7).unwrap(),
+            mode: if region.is_some() { RecordingMode::Region } else { RecordingMode::Fullscreen },
+            region,
+            window_title: None,
+            display: "desktop",
+            resolution: "original",
+        }
+    }
+
+    #[test]
+    fn renders_tokens() {
+        let (dir, stem) = render("{yyyy}/{mm}/{mode}_{date}_{time}_{counter}", &context(None), 7).unwrap();
+        assert_eq!(dir, PathBuf::from("2024").join("03"));
+        assert_eq!(stem, "fullscreen_20240309_140507_007");
+    }
+
+    #[test]
+    fn empty_templates_use_the_default() {
+        let (dir, stem) = render("  ", &context(None), 1).unwrap();
+        assert_eq!(dir, PathBuf::new());
+        assert_eq!(stem, "FlashScreen_20240309_140507");
+    }
+
+    #[test]
+    fn names_regions_at_original_size_after_their_size() {
+        let region = Region { x: 0, y: 0, width: 800, height: 600 };
+        let (_, stem) = render("{resolution}", &context(Some(&region)), 1).unwrap();
+        assert_eq!(stem, "800x600");
+    }
+
+    #[test]
+    fn falls_back_to_the_mode_without_a_window_title() {
+        let (_, stem) = render("{window_title}", &context(None), 1).unwrap();
+        assert_eq!(stem, "fullscreen");
+    }
+
+    #[test]
+    fn sanitizes_every_component() {
+        let (dir, stem) = render("a:b/../CON/x?y.", &context(None), 1).unwrap();
+        assert_eq!(dir, PathBuf::from("a_b").join("_").join("_CON"));
+        assert_eq!(stem, "x_y");
+    }
+
+    #[test]
+    fn leaves_tokens_in_window_titles_alone() {
+        let ctx = TemplateContext {
+            mode: RecordingMode::Window,
+            window_title: Some("{counter} {display}"),
+            ..context(None)
+        };
+        let (_, stem) = render("{window_title}_{counter}_{unknown}", &ctx, 4).unwrap();
+        assert_eq!(stem, "{counter} {display}_004_{unknown}");
+    }
+}
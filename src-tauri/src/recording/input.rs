@@ -0,0 +1,475 @@
+//! Platform input listeners feeding the recording telemetry.
+//!
+//! Linux uses XInput2 raw events on the root window, which arrive no matter
+//! which window has focus; Windows polls the cursor, button and key state.
+
+use anyhow::Result;
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
+use tokio::sync::oneshot::Sender;
+
+use super::telemetry::{CaptureArea, CursorEvent, KeyEvent};
+use crate::media::keystrokes::KeystrokeFilter;
+
+pub use platform::{listen, screen_bounds};
+
+/// What a listener records
+#[derive(Debug, Clone, Copy)]
+pub struct ListenOptions {
+    pub cursor: bool,
+    /// Record key presses through this filter, `None` ignores the keyboard
+    pub keys: Option<KeystrokeFilter>,
+}
+
+/// Input collected by a listener
+#[derive(Debug, Default)]
+pub struct Recorded {
+    pub cursor: Vec<CursorEvent>,
+    pub keys: Vec<KeyEvent>,
+}
+
+/// Milliseconds since `started`
+fn elapsed_ms(started: Instant) -> u64 {
+    started.elapsed().as_millis() as u64
+}
+
+/// Modifier keys, in the order they are shown in labels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+    Super,
+}
+
+impl Modifier {
+    const ALL: [Modifier; 4] = [Modifier::Ctrl, Modifier::Alt, Modifier::Shift, Modifier::Super];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "Ctrl",
+            Modifier::Alt => "Alt",
+            Modifier::Shift => "Shift",
+            Modifier::Super if cfg!(windows) => "Win",
+            Modifier::Super => "Super",
+        }
+    }
+}
+
+/// A key as reported by a platform listener
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Key {
+    Modifier(Modifier),
+    /// AltGr (ISO_Level3_Shift), which selects characters rather than
+    /// making a shortcut
+    AltGr,
+    Named(String),
+}
+
+/// Turns key presses into shortcut labels, applying the privacy filter
+struct KeyTracker {
+    filter: KeystrokeFilter,
+    held: [bool; 4],
+    alt_gr: bool,
+}
+
+impl KeyTracker {
+    fn new(filter: KeystrokeFilter) -> Self {
+        Self {
+            filter,
+            held: [false; 4],
+            alt_gr: false,
+        }
+    }
+
+    /// Track a press or release, returning the label of a shortcut to record
+    fn update(&mut self, key: &Key, down: bool) -> Option<String> {
+        let name = match key {
+            Key::Modifier(modifier) => {
+                let index = Modifier::ALL.iter().position(|m| m == modifier)?;
+                self.held[index] = down;
+                return None;
+            }
+            Key::AltGr => {
+                self.alt_gr = down;
+                return None;
+            }
+            Key::Named(name) if down => name,
+            Key::Named(_) => return None,
+        };
+
+        let held: Vec<Modifier> = Modifier::ALL
+            .iter()
+            .zip(self.held)
+            .filter(|(_, held)| *held)
+            .map(|(m, _)| *m)
+            // Windows reports AltGr as Ctrl+Alt
+            .filter(|m| !(self.alt_gr && matches!(m, Modifier::Ctrl | Modifier::Alt)))
+            .collect();
+
+        // Shift alone only changes what is typed
+        let is_shortcut = held.iter().any(|m| *m != Modifier::Shift);
+        if self.filter == KeystrokeFilter::ShortcutsOnly && !is_shortcut {
+            return None;
+        }
+
+        let mut label: Vec<&str> = held.iter().map(|m| m.label()).collect();
+        label.push(name);
+        Some(label.join("+"))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xinput::{self, ConnectionExt as _};
+    use x11rb::protocol::xproto::ConnectionExt as _;
+    use x11rb::protocol::Event;
+
+    use crate::recording::telemetry::CursorEventKind;
+
+    /// `XIAllMasterDevices`
+    const ALL_MASTER_DEVICES: u16 = 1;
+
+    pub fn screen_bounds() -> Result<CaptureArea> {
+        let (conn, screen) = x11rb::connect(None).map_err(|e| anyhow!("Failed to connect to X server: {}", e))?;
+        let root = &conn.setup().roots[screen];
+        Ok(CaptureArea {
+            x: 0,
+            y: 0,
+            width: root.width_in_pixels as u32,
+            height: root.height_in_pixels as u32,
+        })
+    }
+
+    /// Name of an X keysym, `None` for keys that are never shown
+    fn keysym_key(keysym: u32) -> Option<Key> {
+        let name = match keysym {
+            0xffe1 | 0xffe2 => return Some(Key::Modifier(Modifier::Shift)),
+            0xffe3 | 0xffe4 => return Some(Key::Modifier(Modifier::Ctrl)),
+            0xffe9 | 0xffea => return Some(Key::Modifier(Modifier::Alt)),
+            0xfe03 => return Some(Key::AltGr),
+            0xffe7 | 0xffe8 | 0xffeb | 0xffec => return Some(Key::Modifier(Modifier::Super)),
+            0x20 => String::from("Space"),
+            0x21..=0x7e => (keysym as u8 as char).to_ascii_uppercase().to_string(),
+            0xffbe..=0xffc9 => format!("F{}", keysym - 0xffbe + 1),
+            0xff08 => String::from("Backspace"),
+            0xff09 => String::from("Tab"),
+            0xff0d => String::from("Enter"),
+            0xff1b => String::from("Esc"),
+            0xffff => String::from("Delete"),
+            0xff50 => String::from("Home"),
+            0xff51 => String::from("Left"),
+            0xff52 => String::from("Up"),
+            0xff53 => String::from("Right"),
+            0xff54 => String::from("Down"),
+            0xff55 => String::from("PageUp"),
+            0xff56 => String::from("PageDown"),
+            0xff57 => String::from("End"),
+            0xff63 => String::from("Insert"),
+            _ => return None,
+        };
+        Some(Key::Named(name))
+    }
+
+    pub fn listen(
+        started: Instant,
+        stop: &AtomicBool,
+        options: &ListenOptions,
+        ready: Sender<Result<()>>,
+    ) -> Result<Recorded> {
+        let setup = || -> Result<_> {
+            let (conn, screen) = x11rb::connect(None).map_err(|e| anyhow!("Failed to connect to X server: {}", e))?;
+            let root = conn.setup().roots[screen].root;
+
+            conn.xinput_xi_query_version(2, 0)?
+                .reply()
+                .map_err(|e| anyhow!("XInput2 is not available: {}", e))?;
+
+            let mut mask = xinput::XIEventMask::from(0u32);
+            if options.cursor {
+                mask = mask
+                    | xinput::XIEventMask::RAW_MOTION
+                    | xinput::XIEventMask::RAW_BUTTON_PRESS
+                    | xinput::XIEventMask::RAW_BUTTON_RELEASE;
+            }
+            if options.keys.is_some() {
+                mask = mask | xinput::XIEventMask::RAW_KEY_PRESS | xinput::XIEventMask::RAW_KEY_RELEASE;
+            }
+            conn.xinput_xi_select_events(
+                root,
+                &[xinput::EventMask {
+                    deviceid: ALL_MASTER_DEVICES,
+                    mask: vec![mask],
+                }],
+            )?;
+
+            // Unshifted keysym of every keycode, so labels read `Ctrl+S`
+            let min = conn.setup().min_keycode;
+            let count = conn.setup().max_keycode - min + 1;
+            let mapping = conn.get_keyboard_mapping(min, count)?.reply()?;
+            let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+            let keysym = move |keycode: u32| {
+                let index = keycode.checked_sub(min as u32)? as usize * per_keycode;
+                mapping.keysyms.get(index).copied()
+            };
+
+            conn.flush()?;
+            Ok((conn, root, keysym))
+        };
+
+        let (conn, root, keysym) = match setup() {
+            Ok(setup) => {
+                let _ = ready.send(Ok(()));
+                setup
+            }
+            Err(e) => {
+                let _ = ready.send(Err(e));
+                return Ok(Recorded::default());
+            }
+        };
+
+        let mut recorded = Recorded::default();
+        let mut keys = options.keys.map(KeyTracker::new);
+        let mut last = (i32::MIN, i32::MIN);
+
+        // Start from where the pointer is, even if it never moves
+        if options.cursor {
+            let pointer = conn.query_pointer(root)?.reply()?;
+            last = (pointer.root_x as i32, pointer.root_y as i32);
+            recorded.cursor.push(CursorEvent {
+                t: 0,
+                x: last.0,
+                y: last.1,
+                kind: CursorEventKind::Move,
+            });
+        }
+
+        while !stop.load(Ordering::Relaxed) {
+            let Some(event) = conn.poll_for_event()? else {
+                std::thread::sleep(Duration::from_millis(4));
+                continue;
+            };
+
+            let kind = match event {
+                Event::XinputRawMotion(_) => CursorEventKind::Move,
+                // Buttons 4 to 7 are the scroll wheel
+                Event::XinputRawButtonPress(e) if e.detail <= 3 => CursorEventKind::Down { button: e.detail as u8 },
+                Event::XinputRawButtonRelease(e) if e.detail <= 3 => CursorEventKind::Up { button: e.detail as u8 },
+                Event::XinputRawKeyPress(e) | Event::XinputRawKeyRelease(e) => {
+                    let down = e.event_type == xinput::RAW_KEY_PRESS_EVENT;
+                    // Focused password fields can't be detected through X11, so
+                    // only the shortcut filter protects typed secrets here
+                    let label = match (keys.as_mut(), keysym(e.detail).and_then(keysym_key)) {
+                        (Some(tracker), Some(key)) => tracker.update(&key, down),
+                        _ => None,
+                    };
+                    if let Some(label) = label {
+                        recorded.keys.push(KeyEvent { t: elapsed_ms(started), label });
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+
+            // Raw events carry device deltas, so ask for the absolute position
+            let pointer = conn.query_pointer(root)?.reply()?;
+            let (x, y) = (pointer.root_x as i32, pointer.root_y as i32);
+            if kind == CursorEventKind::Move && (x, y) == last {
+                continue;
+            }
+            last = (x, y);
+
+            recorded.cursor.push(CursorEvent {
+                t: elapsed_ms(started),
+                x,
+                y,
+                kind,
+            });
+        }
+
+        Ok(recorded)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use windows_sys::Win32::Foundation::POINT;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, VK_LBUTTON, VK_MBUTTON, VK_RBUTTON};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetCursorPos, GetGUIThreadInfo, GetSystemMetrics, GetWindowLongW, ES_PASSWORD, GUITHREADINFO, GWL_STYLE,
+        SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+    };
+
+    use crate::recording::telemetry::CursorEventKind;
+
+    /// How often the cursor and keys are sampled
+    const POLL_INTERVAL: Duration = Duration::from_millis(8);
+    /// AltGr arrives as right Alt together with a synthetic left Ctrl
+    const VK_LCONTROL: i32 = 0xA2;
+    const VK_RMENU: i32 = 0xA5;
+
+    pub fn screen_bounds() -> Result<CaptureArea> {
+        // gdigrab's `desktop` is the virtual screen spanning all monitors
+        let (x, y, width, height) = unsafe {
+            (
+                GetSystemMetrics(SM_XVIRTUALSCREEN),
+                GetSystemMetrics(SM_YVIRTUALSCREEN),
+                GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            )
+        };
+        if width <= 0 || height <= 0 {
+            return Err(anyhow!("Failed to query the screen size"));
+        }
+        Ok(CaptureArea {
+            x,
+            y,
+            width: width as u32,
+            height: height as u32,
+        })
+    }
+
+    /// Virtual keys that are shown, with their labels
+    fn virtual_keys() -> Vec<(i32, Key)> {
+        let mut keys = vec![
+            (0xA0, Key::Modifier(Modifier::Shift)),
+            (0xA1, Key::Modifier(Modifier::Shift)),
+            (VK_LCONTROL, Key::Modifier(Modifier::Ctrl)),
+            (0xA3, Key::Modifier(Modifier::Ctrl)),
+            (0xA4, Key::Modifier(Modifier::Alt)),
+            (VK_RMENU, Key::Modifier(Modifier::Alt)),
+            (0x5B, Key::Modifier(Modifier::Super)),
+            (0x5C, Key::Modifier(Modifier::Super)),
+        ];
+        let named = [
+            (0x08, "Backspace"),
+            (0x09, "Tab"),
+            (0x0D, "Enter"),
+            (0x1B, "Esc"),
+            (0x20, "Space"),
+            (0x21, "PageUp"),
+            (0x22, "PageDown"),
+            (0x23, "End"),
+            (0x24, "Home"),
+            (0x25, "Left"),
+            (0x26, "Up"),
+            (0x27, "Right"),
+            (0x28, "Down"),
+            (0x2D, "Insert"),
+            (0x2E, "Delete"),
+        ];
+        keys.extend(named.iter().map(|(vk, name)| (*vk, Key::Named(name.to_string()))));
+        keys.extend((0x30..=0x39).chain(0x41..=0x5A).map(|vk| (vk, Key::Named((vk as u8 as char).to_string()))));
+        keys.extend((0x70..=0x7B).map(|vk| (vk, Key::Named(format!("F{}", vk - 0x70 + 1)))));
+        keys
+    }
+
+    /// Whether the focused control is a classic password edit box
+    fn password_focused() -> bool {
+        unsafe {
+            let mut info: GUITHREADINFO = std::mem::zeroed();
+            info.cbSize = std::mem::size_of::<GUITHREADINFO>() as u32;
+            if GetGUIThreadInfo(0, &mut info) == 0 || info.hwndFocus.is_null() {
+                return false;
+            }
+            GetWindowLongW(info.hwndFocus, GWL_STYLE) & ES_PASSWORD != 0
+        }
+    }
+
+    pub fn listen(
+        started: Instant,
+        stop: &AtomicBool,
+        options: &ListenOptions,
+        ready: Sender<Result<()>>,
+    ) -> Result<Recorded> {
+        let _ = ready.send(Ok(()));
+
+        let buttons = [(1u8, VK_LBUTTON), (2, VK_MBUTTON), (3, VK_RBUTTON)];
+        let mut pressed = [false; 3];
+        let mut last = POINT { x: i32::MIN, y: i32::MIN };
+
+        let virtual_keys = if options.keys.is_some() { virtual_keys() } else { Vec::new() };
+        let mut keys_down = vec![false; virtual_keys.len()];
+        let mut alt_gr = false;
+        let mut keys = options.keys.map(KeyTracker::new);
+        let mut recorded = Recorded::default();
+
+        while !stop.load(Ordering::Relaxed) {
+            let t = elapsed_ms(started);
+            let mut point = POINT { x: 0, y: 0 };
+
+            if options.cursor && unsafe { GetCursorPos(&mut point) } != 0 {
+                if (point.x, point.y) != (last.x, last.y) {
+                    recorded.cursor.push(CursorEvent { t, x: point.x, y: point.y, kind: CursorEventKind::Move });
+                    last = point;
+                }
+
+                for (i, (button, key)) in buttons.iter().enumerate() {
+                    // The high bit is set while the button is down
+                    let down = unsafe { GetAsyncKeyState(*key as i32) } < 0;
+                    if down != pressed[i] {
+                        pressed[i] = down;
+                        let kind = if down {
+                            CursorEventKind::Down { button: *button }
+                        } else {
+                            CursorEventKind::Up { button: *button }
+                        };
+                        recorded.cursor.push(CursorEvent { t, x: point.x, y: point.y, kind });
+                    }
+                }
+            }
+
+            if let Some(tracker) = keys.as_mut() {
+                let hidden = password_focused();
+                let down = unsafe { GetAsyncKeyState(VK_RMENU) < 0 && GetAsyncKeyState(VK_LCONTROL) < 0 };
+                if down != alt_gr {
+                    alt_gr = down;
+                    tracker.update(&Key::AltGr, down);
+                }
+                for (i, (vk, key)) in virtual_keys.iter().enumerate() {
+                    let down = unsafe { GetAsyncKeyState(*vk) } < 0;
+                    if down == keys_down[i] {
+                        continue;
+                    }
+                    keys_down[i] = down;
+                    if let Some(label) = tracker.update(key, down).filter(|_| !hidden) {
+                        recorded.keys.push(KeyEvent { t, label });
+                    }
+                }
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        Ok(recorded)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod platform {
+    use super::*;
+    use anyhow::anyhow;
+
+    pub fn screen_bounds() -> Result<CaptureArea> {
+        Err(anyhow!("Input telemetry is not supported on this platform"))
+    }
+
+    pub fn listen(
+        _started: Instant,
+        _stop: &AtomicBool,
+        _options: &ListenOptions,
+        ready: Sender<Result<()>>,
+    ) -> Result<Recorded> {
+        let _ = ready.send(Err(anyhow!("Input telemetry is not supported on this platform")));
+        Ok(Recorded::default())
+    }
+}
@@ -1,5 +1,10 @@
+pub mod disk;
 pub mod ffmpeg;
+pub mod filename;
+pub mod input;
+pub mod profiles;
 pub mod state;
+pub mod telemetry;
 
-pub use ffmpeg::FFmpegRecorder;
+pub use ffmpeg::{FFmpegRecorder, RecordingOptions};
 pub use state::{RecordingState, RecordingStatus};
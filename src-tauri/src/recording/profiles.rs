@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::state::{RecordingMode, RecordingSources, Region};
+use crate::commands::settings::{OutputSettings, Settings};
+
+/// Version of the profile export format
+const PROFILES_VERSION: u32 = 1;
+
+/// Named bundle of what to capture and how to encode it, e.g.
+/// "Bug report: region, mic, 30 fps, small MKV"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordingProfile {
+    /// Stable id, derived from the name when the profile is created
+    pub id: String,
+    pub name: String,
+    pub mode: RecordingMode,
+    /// Area recorded in region mode
+    pub region: Option<Region>,
+    pub sources: RecordingSources,
+    pub camera_device_id: Option<String>,
+    /// Id of the encoder profile used for live capture
+    pub encoder_profile: String,
+    pub filename_template: String,
+    pub resolution: String,
+    pub frame_rate: u32,
+    /// Global hotkey starting a recording with this profile, empty for none
+    pub hotkey: String,
+}
+
+impl Default for RecordingProfile {
+    fn default() -> Self {
+        let output = OutputSettings::default();
+        Self {
+            id: String::new(),
+            name: String::new(),
+            mode: RecordingMode::default(),
+            region: None,
+            sources: RecordingSources::default(),
+            camera_device_id: None,
+            encoder_profile: output.encoder_profile,
+            filename_template: output.filename_template,
+            resolution: output.resolution,
+            frame_rate: output.frame_rate,
+            hotkey: String::new(),
+        }
+    }
+}
+
+impl RecordingProfile {
+    /// `settings` with the output options of this profile
+    pub fn apply(&self, settings: &Settings) -> Settings {
+        let mut settings = settings.clone();
+        settings.output.encoder_profile = self.encoder_profile.clone();
+        settings.output.filename_template = self.filename_template.clone();
+        settings.output.resolution = self.resolution.clone();
+        settings.output.frame_rate = self.frame_rate;
+        if self.camera_device_id.is_some() {
+            settings.camera.device_id = self.camera_device_id.clone();
+        }
+        settings
+    }
+}
+
+/// File written by profile export, holding one or more profiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileBundle {
+    pub version: u32,
+    pub profiles: Vec<RecordingProfile>,
+}
+
+impl ProfileBundle {
+    pub fn new(profiles: Vec<RecordingProfile>) -> Self {
+        Self {
+            version: PROFILES_VERSION,
+            profiles,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let bundle: Self =
+            serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+        if bundle.version > PROFILES_VERSION {
+            return Err(anyhow!("{} was exported by a newer version of FlashScreen", path.display()));
+        }
+        Ok(bundle)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Id for a profile named `name` that none of `existing` uses, e.g. `bug-report-2`
+pub fn unique_id(name: &str, existing: &[RecordingProfile]) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() { String::from("profile") } else { slug };
+
+    let taken = |id: &str| existing.iter().any(|p| p.id == id);
+    let mut id = base.clone();
+    let mut n = 2;
+    while taken(&id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
@@ -0,0 +1,156 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use super::input::{self, ListenOptions, Recorded};
+
+/// Version of the telemetry file format
+const TELEMETRY_VERSION: u32 = 1;
+
+/// Screen area captured into the video, in screen coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureArea {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// What happened to the pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CursorEventKind {
+    Move,
+    /// Button 1 is left, 2 middle, 3 right
+    Down { button: u8 },
+    Up { button: u8 },
+}
+
+/// Pointer event in screen coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorEvent {
+    /// Milliseconds since the recording started
+    pub t: u64,
+    pub x: i32,
+    pub y: i32,
+    #[serde(flatten)]
+    pub kind: CursorEventKind,
+}
+
+/// A pressed key or shortcut, already passed through the privacy filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyEvent {
+    /// Milliseconds since the recording started
+    pub t: u64,
+    /// e.g. `Ctrl+Shift+S`
+    pub label: String,
+}
+
+/// Input recorded alongside a video, stored as `<stem>.telemetry.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Telemetry {
+    pub version: u32,
+    pub area: CaptureArea,
+    pub cursor: Vec<CursorEvent>,
+    #[serde(default)]
+    pub keys: Vec<KeyEvent>,
+}
+
+impl Telemetry {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string(self)?;
+        std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Telemetry file of a recording session
+pub fn telemetry_path(dir: &Path, stem: &str) -> PathBuf {
+    dir.join(format!("{}.telemetry.json", stem))
+}
+
+/// File stem of the session a recording or one of its numbered parts belongs to
+pub fn session_stem(video: &Path) -> Option<String> {
+    let stem = video.file_stem()?.to_string_lossy().to_string();
+    Some(match stem.rsplit_once("_part") {
+        Some((base, n)) if n.len() == 3 && n.chars().all(|c| c.is_ascii_digit()) => base.to_string(),
+        _ => stem,
+    })
+}
+
+/// Telemetry file belonging to a recording or one of its numbered parts
+pub fn telemetry_for(video: &Path) -> Option<PathBuf> {
+    let path = telemetry_path(video.parent()?, &session_stem(video)?);
+    path.exists().then_some(path)
+}
+
+/// Records pointer and keyboard input on a background thread for one session
+pub struct TelemetryRecorder {
+    path: PathBuf,
+    area: CaptureArea,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<Result<Recorded>>,
+}
+
+impl TelemetryRecorder {
+    /// Start listening; `area` defaults to the whole desktop
+    pub async fn start(path: PathBuf, area: Option<CaptureArea>, options: ListenOptions) -> Result<Self> {
+        let area = match area {
+            Some(area) => area,
+            None => input::screen_bounds()?,
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let started = Instant::now();
+        let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+
+        let thread = {
+            let stop = stop.clone();
+            std::thread::spawn(move || input::listen(started, &stop, &options, ready_tx))
+        };
+
+        // Fail early when the platform has no usable input source, e.g. Wayland
+        match ready_rx.await {
+            Ok(Ok(())) => Ok(Self { path, area, stop, thread }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(anyhow!("Input listener stopped unexpectedly")),
+        }
+    }
+
+    /// Stop listening and write the telemetry file
+    pub fn finish(self) -> Result<PathBuf> {
+        self.stop.store(true, Ordering::Relaxed);
+        let recorded = self
+            .thread
+            .join()
+            .map_err(|_| anyhow!("Input listener panicked"))??;
+
+        let telemetry = Telemetry {
+            version: TELEMETRY_VERSION,
+            area: self.area,
+            cursor: recorded.cursor,
+            keys: recorded.keys,
+        };
+        telemetry.save(&self.path)?;
+        Ok(self.path)
+    }
+
+    /// Stop listening without writing anything
+    pub fn discard(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.thread.join();
+    }
+}
@@ -1,8 +1,9 @@
 import { useEffect, useState } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { AppLayout } from "@/components/layout";
-import { useRecordingStore } from "@/stores";
-import { startRecording, stopRecording, pauseRecording, resumeRecording, cancelRecording, checkFFmpegAvailable } from "@/services/recording";
+import { useRecordingStore, useSettingsStore } from "@/stores";
+import type { SettingsChanged } from "@/types";
+import { startRecording, startRecordingWithProfile, stopRecording, pauseRecording, resumeRecording, cancelRecording, checkFFmpegAvailable } from "@/services/recording";
 
 function App() {
   const { status } = useRecordingStore();
@@ -74,6 +75,20 @@ function App() {
         }
       }),
       
+      // Profile hotkeys - start with that profile
+      listen<string>("hotkey-start-profile", async (event) => {
+        const store = useRecordingStore.getState();
+        if (store.status !== "idle") return;
+
+        try {
+          await startRecordingWithProfile(event.payload);
+          store.setStatus("recording");
+        } catch (err) {
+          console.error("Hotkey profile start error:", err);
+          store.setStatus("idle");
+        }
+      }),
+
       // F4 - Toggle Camera
       listen("hotkey-toggle-camera", () => {
         useRecordingStore.getState().toggleSource("camera");
@@ -85,6 +100,17 @@ function App() {
     };
   }, []);
 
+  // Pick up settings changed by the backend, another window or an external edit
+  useEffect(() => {
+    const unlisten = listen<SettingsChanged>("settings-changed", (event) => {
+      useSettingsStore.getState().setSettings(event.payload.settings);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Recording duration timer
   useEffect(() => {
     let interval: ReturnType<typeof setInterval> | null = null;
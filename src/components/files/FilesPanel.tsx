@@ -91,6 +91,7 @@ interface FileCardProps {
     resolution: string;
     frameRate: number;
     thumbnail?: string;
+    parts?: string[];
   };
 }
 
@@ -142,6 +143,9 @@ function FileCard({ file }: FileCardProps) {
             </p>
             <p className="text-[var(--text-xs)] text-[var(--text-tertiary)] mt-1">
               {file.resolution} · {file.frameRate}fps · {formatFileSize(file.size)}
+              {file.parts && file.parts.length > 1 && (
+                <> · {t("files.parts", { count: file.parts.length })}</>
+              )}
             </p>
           </div>
 
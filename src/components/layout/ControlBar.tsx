@@ -2,6 +2,7 @@ import { useState } from "react";
 import { Monitor, AppWindow, Scan, Mic, MicOff, Volume2, VolumeX, Video, VideoOff, Circle, Square, Pause, Play, Settings, Loader2 } from "lucide-react";
 import { useRecordingStore, useUIStore } from "@/stores";
 import { startRecording, stopRecording, pauseRecording, resumeRecording, cancelRecording } from "@/services/recording";
+import { errorMessage } from "@/services/errors";
 import type { RecordingMode, RecordingSources } from "@/types";
 
 export function ControlBar() {
@@ -38,7 +39,7 @@ export function ControlBar() {
       }
     } catch (err) {
       console.error("Recording error:", err);
-      setError(String(err));
+      setError(errorMessage(err));
       setStatus("idle");
     } finally {
       setIsLoading(false);
@@ -58,7 +59,7 @@ export function ControlBar() {
       }
     } catch (err) {
       console.error("Pause/Resume error:", err);
-      setError(String(err));
+      setError(errorMessage(err));
     }
   };
 
@@ -71,7 +72,7 @@ export function ControlBar() {
       useRecordingStore.getState().setDuration(0);
     } catch (err) {
       console.error("Cancel error:", err);
-      setError(String(err));
+      setError(errorMessage(err));
     }
   };
 
@@ -0,0 +1,15 @@
+import type { AppError } from "@/types";
+
+/**
+ * Whether a rejected command returned a structured error
+ */
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === "object" && err !== null && "code" in err && "message" in err;
+}
+
+/**
+ * Message to show for a rejected command
+ */
+export function errorMessage(err: unknown): string {
+  return isAppError(err) ? err.message : String(err);
+}
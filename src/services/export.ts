@@ -0,0 +1,84 @@
+import { invoke } from "@tauri-apps/api/core";
+import type {
+  AnimationOptions,
+  AnimationResult,
+  AudioTrack,
+  EncoderProfile,
+  ExportJob,
+  ExtractAudioOptions,
+} from "@/types";
+
+/**
+ * Get the encoder profiles recordings can be exported to
+ */
+export async function listEncoderProfiles(): Promise<EncoderProfile[]> {
+  return invoke<EncoderProfile[]>("list_encoder_profiles");
+}
+
+/**
+ * Queue an export of a recording, progress is reported by "export-job-updated"
+ */
+export async function exportRecording(
+  path: string,
+  profileId: string
+): Promise<ExportJob> {
+  return invoke<ExportJob>("export_recording", { path, profileId });
+}
+
+/**
+ * Get all export jobs of this session
+ */
+export async function getExportJobs(): Promise<ExportJob[]> {
+  return invoke<ExportJob[]>("get_export_jobs");
+}
+
+/**
+ * Cancel a queued or running export job
+ */
+export async function cancelExportJob(id: number): Promise<ExportJob> {
+  return invoke<ExportJob>("cancel_export_job", { id });
+}
+
+/**
+ * Queue a failed or cancelled export job again
+ */
+export async function retryExportJob(id: number): Promise<ExportJob> {
+  return invoke<ExportJob>("retry_export_job", { id });
+}
+
+/**
+ * Estimate the size of a GIF or APNG export without encoding it
+ */
+export async function estimateAnimation(
+  path: string,
+  options: AnimationOptions
+): Promise<AnimationResult> {
+  return invoke<AnimationResult>("estimate_animation", { path, options });
+}
+
+/**
+ * Export a range of a recording as an optimized animated GIF or APNG
+ */
+export async function exportAnimation(
+  path: string,
+  options: AnimationOptions
+): Promise<AnimationResult> {
+  return invoke<AnimationResult>("export_animation", { path, options });
+}
+
+/**
+ * Get the audio tracks of a recording
+ */
+export async function getAudioTracks(path: string): Promise<AudioTrack[]> {
+  return invoke<AudioTrack[]>("get_audio_tracks", { path });
+}
+
+/**
+ * Extract audio tracks of a recording, returns the written files
+ */
+export async function extractAudio(
+  path: string,
+  options: ExtractAudioOptions
+): Promise<string[]> {
+  return invoke<string[]>("extract_audio", { path, options });
+}
@@ -1,5 +1,12 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { RecordingFile } from "@/types";
+import type {
+  BatchResult,
+  CursorEffects,
+  DeleteResult,
+  RecordingFile,
+  TimeRange,
+  ZoomKeyframe,
+} from "@/types";
 
 /**
  * Get list of recording files
@@ -9,10 +16,17 @@ export async function getRecordings(): Promise<RecordingFile[]> {
 }
 
 /**
- * Delete a recording file
+ * Move a recording file to the trash
  */
-export async function deleteRecording(path: string): Promise<void> {
-  return invoke("delete_recording", { path });
+export async function deleteRecording(path: string): Promise<DeleteResult> {
+  return invoke<DeleteResult>("delete_recording", { path });
+}
+
+/**
+ * Restore a recording deleted within the undo window
+ */
+export async function undoDeleteRecording(undoId: number): Promise<string> {
+  return invoke<string>("undo_delete_recording", { undoId });
 }
 
 /**
@@ -38,3 +52,91 @@ export async function showInFolder(path: string): Promise<void> {
 export async function openFile(path: string): Promise<void> {
   return invoke("open_file", { path });
 }
+
+/**
+ * Move many recordings to the trash
+ */
+export async function deleteRecordings(paths: string[]): Promise<BatchResult> {
+  return invoke<BatchResult>("delete_recordings", { paths });
+}
+
+/**
+ * Move many recordings to a folder inside the output directory
+ */
+export async function moveRecordings(
+  paths: string[],
+  destination: string
+): Promise<BatchResult> {
+  return invoke<BatchResult>("move_recordings", { paths, destination });
+}
+
+/**
+ * Copy many recordings to an export folder
+ */
+export async function exportRecordings(
+  paths: string[],
+  destination: string
+): Promise<BatchResult> {
+  return invoke<BatchResult>("export_recordings", { paths, destination });
+}
+
+/**
+ * Create a zip archive of many recordings with a metadata manifest
+ */
+export async function zipRecordings(
+  paths: string[],
+  destination: string
+): Promise<BatchResult> {
+  return invoke<BatchResult>("zip_recordings", { paths, destination });
+}
+
+/**
+ * Trim a recording to one or more ranges, returning the output path
+ */
+export async function trimRecording(
+  path: string,
+  ranges: TimeRange[],
+  replace: boolean
+): Promise<string> {
+  return invoke<string>("trim_recording", { path, ranges, replace });
+}
+
+/**
+ * Re-render the cursor effects of a recording from its telemetry,
+ * using the recording settings unless effects are given
+ */
+export async function renderCursorEffects(
+  path: string,
+  effects?: CursorEffects
+): Promise<string> {
+  return invoke<string>("render_cursor_effects", { path, effects });
+}
+
+/**
+ * Zoom timeline of a recording: keyframes generated from its clicks,
+ * merged with the saved manual points
+ */
+export async function getZoomKeyframes(path: string): Promise<ZoomKeyframe[]> {
+  return invoke<ZoomKeyframe[]>("get_zoom_keyframes", { path });
+}
+
+/**
+ * Save the manual zoom points of a recording; an empty list removes them
+ */
+export async function saveZoomOverrides(
+  path: string,
+  points: ZoomKeyframe[]
+): Promise<void> {
+  return invoke("save_zoom_overrides", { path, points });
+}
+
+/**
+ * Render a zoomed copy of a recording, following its zoom timeline
+ * unless keyframes are given. Returns the written files
+ */
+export async function renderAutoZoom(
+  path: string,
+  keyframes?: ZoomKeyframe[]
+): Promise<string[]> {
+  return invoke<string[]>("render_auto_zoom", { path, keyframes });
+}
@@ -1,3 +1,5 @@
 export * from "./recording";
 export * from "./settings";
 export * from "./files";
+export * from "./export";
+export * from "./errors";
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { RecordingMode, RecordingSources, Region, RecordingStatus } from "@/types";
+import type { FfmpegCapabilities, RecordingMode, RecordingProfile, RecordingSources, Region, RecordingStatus } from "@/types";
 
 export interface RecordingState {
   status: RecordingStatus;
@@ -73,3 +73,64 @@ export async function cancelRecording(): Promise<void> {
 export async function checkFFmpegAvailable(): Promise<boolean> {
   return invoke<boolean>("check_ffmpeg_available");
 }
+
+/**
+ * Get the encoders, muxers, filters and devices of the FFmpeg sidecar,
+ * probing it again when refresh is set
+ */
+export async function getFfmpegCapabilities(refresh = false): Promise<FfmpegCapabilities> {
+  return invoke<FfmpegCapabilities>("get_ffmpeg_capabilities", { refresh });
+}
+
+/**
+ * List the recording profiles
+ */
+export async function getRecordingProfiles(): Promise<RecordingProfile[]> {
+  return invoke<RecordingProfile[]>("get_recording_profiles");
+}
+
+/**
+ * Create a profile, or replace the one with the same id; an empty id creates one
+ */
+export async function saveRecordingProfile(
+  profile: RecordingProfile
+): Promise<RecordingProfile> {
+  return invoke<RecordingProfile>("save_recording_profile", { profile });
+}
+
+/**
+ * Delete a recording profile
+ */
+export async function deleteRecordingProfile(id: string): Promise<void> {
+  return invoke("delete_recording_profile", { id });
+}
+
+/**
+ * Export recording profiles to a JSON file, all of them when no ids are given
+ */
+export async function exportRecordingProfiles(
+  path: string,
+  ids?: string[]
+): Promise<void> {
+  return invoke("export_recording_profiles", { path, ids });
+}
+
+/**
+ * Add the profiles of an exported JSON file
+ */
+export async function importRecordingProfiles(
+  path: string
+): Promise<RecordingProfile[]> {
+  return invoke<RecordingProfile[]>("import_recording_profiles", { path });
+}
+
+/**
+ * Start recording with a profile, optionally overriding its region or picking a window
+ */
+export async function startRecordingWithProfile(
+  id: string,
+  region: Region | null = null,
+  windowId: string | null = null
+): Promise<string> {
+  return invoke<string>("start_recording_with_profile", { id, region, windowId });
+}
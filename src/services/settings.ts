@@ -1,11 +1,11 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Settings } from "@/types";
+import type { OutputDirectoryChanged, PreviousRecordings, Settings, SettingsReport, SettingsSection, SettingsView, SoundCue } from "@/types";
 
 /**
- * Get current settings from backend
+ * Get current settings from backend, with where each value comes from
  */
-export async function getSettings(): Promise<Settings> {
-  return invoke<Settings>("get_settings");
+export async function getSettings(): Promise<SettingsView> {
+  return invoke<SettingsView>("get_settings");
 }
 
 /**
@@ -15,6 +15,49 @@ export async function updateSettings(settings: Settings): Promise<void> {
   return invoke("update_settings", { newSettings: settings });
 }
 
+/**
+ * Change only the given values, as a JSON merge patch; returns the changed keys
+ */
+export async function patchSettings(patch: object): Promise<string[]> {
+  return invoke<string[]>("patch_settings", { patch });
+}
+
+/**
+ * Reset one settings section to its defaults; returns the changed keys
+ */
+export async function resetSettings(section: SettingsSection): Promise<string[]> {
+  return invoke<string[]>("reset_settings", { section });
+}
+
+/**
+ * Zip the logs, settings, FFmpeg build and system details into a file for
+ * bug reports; resolves to the path written
+ */
+export async function exportDiagnostics(path: string): Promise<string> {
+  return invoke<string>("export_diagnostics", { path });
+}
+
+/**
+ * Write the current settings to a file
+ */
+export async function exportSettings(path: string): Promise<void> {
+  return invoke("export_settings", { path });
+}
+
+/**
+ * Replace the settings with a previously exported file; returns the changed keys
+ */
+export async function importSettings(path: string): Promise<string[]> {
+  return invoke<string[]>("import_settings", { path });
+}
+
+/**
+ * Get the problems found loading the settings file, and its backup
+ */
+export async function getSettingsProblems(): Promise<SettingsReport> {
+  return invoke<SettingsReport>("get_settings_problems");
+}
+
 /**
  * Get output directory
  */
@@ -23,8 +66,23 @@ export async function getOutputDirectory(): Promise<string> {
 }
 
 /**
- * Select output directory using file dialog
+ * Select output directory using file dialog and save it; recordings in the
+ * previous directory are kept, moved or indexed. Null when cancelled.
+ */
+export async function selectOutputDirectory(
+  previous: PreviousRecordings = "keep"
+): Promise<OutputDirectoryChanged | null> {
+  return invoke<OutputDirectoryChanged | null>("select_output_directory", {
+    previous,
+  });
+}
+
+/**
+ * Preview a sound cue at the configured volume, optionally with another file
  */
-export async function selectOutputDirectory(): Promise<string | null> {
-  return invoke<string | null>("select_output_directory");
+export async function previewSoundCue(
+  cue: SoundCue,
+  file?: string
+): Promise<void> {
+  return invoke("preview_sound_cue", { cue, file });
 }
@@ -3,11 +3,20 @@ import { persist } from "zustand/middleware";
 import type { Settings } from "@/types";
 
 const defaultSettings: Settings = {
+  version: 2,
   output: {
     directory: "",
     resolution: "1080p",
     frameRate: 60,
     format: "mp4",
+    maxFileSizeMb: 0,
+    splitDurationMin: 0,
+    lowDiskWarningMb: 2048,
+    minFreeSpaceMb: 500,
+    filenameTemplate: "FlashScreen_{date}_{time}",
+    encoderProfile: "realtime",
+    exportConcurrency: 2,
+    libraryDirectories: [],
   },
   hotkeys: {
     startStop: "F1",
@@ -22,6 +31,38 @@ const defaultSettings: Settings = {
     highlightClicks: false,
     playStartSound: true,
     playEndSound: true,
+    sounds: {
+      playPauseSound: true,
+      playErrorSound: true,
+      volume: 0.6,
+      startFile: null,
+      stopFile: null,
+      pauseFile: null,
+      errorFile: null,
+    },
+    cursorEffects: {
+      smoothing: 0.6,
+      clickRipples: false,
+      spotlight: false,
+      cursorScale: 1,
+      rippleColor: "#FFC83D",
+      spotlightRadius: 160,
+    },
+    keystrokes: {
+      enabled: false,
+      filter: "shortcutsOnly",
+      position: "bottom-center",
+      fontSize: 42,
+      textColor: "#FFFFFF",
+      backgroundColor: "#202020",
+      displayMs: 1200,
+    },
+    autoZoom: {
+      enabled: false,
+      scale: 2,
+      holdMs: 1500,
+      transitionMs: 600,
+    },
   },
   camera: {
     deviceId: null,
@@ -34,7 +75,34 @@ const defaultSettings: Settings = {
     launchAtStartup: false,
     minimizeToTray: true,
     showPreviewAfterRecording: true,
+    notifications: "all",
+    logLevel: "info",
   },
+  audio: {
+    microphone: {
+      enabled: false,
+      mode: "live",
+      highpassHz: 80,
+      noiseSuppression: "fft",
+      noiseReductionDb: 12,
+      rnnModel: null,
+      compressor: true,
+      loudnorm: true,
+      targetLufs: -16,
+    },
+    systemAudio: {
+      enabled: false,
+      mode: "live",
+      highpassHz: 0,
+      noiseSuppression: "off",
+      noiseReductionDb: 12,
+      rnnModel: null,
+      compressor: false,
+      loudnorm: true,
+      targetLufs: -16,
+    },
+  },
+  profiles: [],
   isFirstLaunch: true,
 };
 
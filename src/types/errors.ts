@@ -0,0 +1,46 @@
+import type { SettingsLayer, SettingsProblem } from "./settings";
+
+/** Stable identifiers of the errors commands reject with */
+export type AppErrorCode =
+  | "FFMPEG_MISSING"
+  | "FFMPEG_FAILED"
+  | "FFMPEG_UNSUPPORTED"
+  | "ALREADY_RECORDING"
+  | "NOT_RECORDING"
+  | "DISK_FULL"
+  | "PERMISSION_DENIED"
+  | "NOT_FOUND"
+  | "ALREADY_EXISTS"
+  | "OUTSIDE_LIBRARY"
+  | "INVALID_NAME"
+  | "INVALID_SETTINGS"
+  | "SETTING_LOCKED"
+  | "IO_ERROR"
+  | "UNKNOWN";
+
+/** Context of an error, depending on its code */
+export interface AppErrorDetails {
+  /** File or folder involved */
+  path?: string;
+  /** Rejected file name, for INVALID_NAME */
+  name?: string;
+  /** Last lines FFmpeg wrote, for FFMPEG_FAILED */
+  stderr?: string;
+  /** Encoders, muxers, filters or devices the FFmpeg build lacks */
+  missing?: string[];
+  /** For DISK_FULL */
+  availableMb?: number;
+  requiredMb?: number;
+  /** For INVALID_SETTINGS */
+  problems?: SettingsProblem[];
+  /** For SETTING_LOCKED */
+  key?: string;
+  layer?: SettingsLayer;
+}
+
+/** Error every command rejects with */
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+  details: AppErrorDetails | null;
+}
@@ -0,0 +1,99 @@
+import type { TimeRange } from "./files";
+
+export interface EncoderProfile {
+  id: string;
+  name: string;
+  extension: string;
+  format: string;
+  /** null drops the video stream */
+  videoCodec: string | null;
+  preset: string | null;
+  tune: string | null;
+  crf: number | null;
+  maxHeight: number | null;
+  fps: number | null;
+  filter: string | null;
+  /** null drops the audio streams */
+  audioCodec: string | null;
+  audioBitrate: string | null;
+}
+
+export type StreamKind = "video" | "audio" | "subtitle";
+
+export interface FfmpegEncoder {
+  name: string;
+  kind: StreamKind;
+  description: string;
+}
+
+/** What the bundled FFmpeg build supports */
+export interface FfmpegCapabilities {
+  /** First line of `ffmpeg -version` */
+  version: string;
+  encoders: FfmpegEncoder[];
+  muxers: string[];
+  filters: string[];
+  /** Capture devices, e.g. "gdigrab" */
+  inputDevices: string[];
+  outputDevices: string[];
+}
+
+export type JobStatus = "queued" | "running" | "completed" | "failed" | "cancelled";
+
+export interface ExportJob {
+  id: number;
+  source: string;
+  output: string;
+  profile: EncoderProfile;
+  status: JobStatus;
+  /** Fraction done, from 0 to 1 */
+  progress: number;
+  attempts: number;
+  error: string | null;
+}
+
+export type AnimationFormat = "gif" | "apng";
+
+export type Dither = "none" | "bayer" | "floydSteinberg" | "sierra";
+
+export interface AnimationOptions {
+  format: AnimationFormat;
+  range: TimeRange;
+  fps: number;
+  /** Output width in pixels, the height keeps the aspect ratio */
+  width: number;
+  dither: Dither;
+  /** Drop frames that barely differ from the previous one */
+  dedupe: boolean;
+}
+
+export interface AnimationResult {
+  /** null for estimates */
+  path: string | null;
+  width: number;
+  height: number;
+  frames: number;
+  estimatedSize: number;
+  /** Size of the written file, null for estimates */
+  actualSize: number | null;
+}
+
+export interface AudioTrack {
+  /** Position among the audio streams */
+  index: number;
+  codec: string;
+  /** e.g. "Mixed", "System audio" or "Microphone" */
+  title: string | null;
+}
+
+export type AudioFormat = "wav" | "flac" | "mp3" | "opus";
+
+export interface ExtractAudioOptions {
+  format: AudioFormat;
+  /** Track indexes to extract, the first track when empty */
+  tracks: number[];
+  /** Write every track to its own file instead of mixing them */
+  separate: boolean;
+  /** Normalize to this integrated loudness in LUFS, e.g. -16 */
+  loudness: number | null;
+}
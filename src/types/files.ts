@@ -1,3 +1,5 @@
+import type { AppError } from "./errors";
+
 export interface RecordingFile {
   path: string;
   name: string;
@@ -7,4 +9,45 @@ export interface RecordingFile {
   frameRate: number;
   createdAt: string;
   thumbnail?: string;
+  /** Paths of all parts when the recording was split, in order */
+  parts: string[];
+  /** "encoding" while an export job is reading or writing the file */
+  status: "ready" | "encoding";
+}
+
+export interface DeleteResult {
+  /** Pass to undoDeleteRecording to restore the recording */
+  undoId: number;
+  /** Seconds left to undo */
+  undoSeconds: number;
+}
+
+export interface BatchItemResult {
+  path: string;
+  /** New location of the recording, for move and export */
+  output: string | null;
+  error: AppError | null;
+}
+
+export interface BatchResult {
+  items: BatchItemResult[];
+  succeeded: number;
+  failed: number;
+  undoId: number | null;
+  undoSeconds: number | null;
+  /** Path of the created archive, for zip */
+  archive: string | null;
+}
+
+export interface BatchProgress {
+  operation: "delete" | "move" | "export" | "zip";
+  completed: number;
+  total: number;
+  path: string;
+}
+
+/** A time range in seconds */
+export interface TimeRange {
+  start: number;
+  end: number;
 }
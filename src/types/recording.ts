@@ -31,3 +31,22 @@ export interface RecordingState {
   sources: RecordingSources;
   outputPath: string | null;
 }
+
+/** Named preset of mode, sources and output options */
+export interface RecordingProfile {
+  /** Stable id, derived from the name when the profile is created */
+  id: string;
+  name: string;
+  mode: RecordingMode;
+  /** Area recorded in region mode */
+  region: Region | null;
+  sources: RecordingSources;
+  cameraDeviceId: string | null;
+  /** Id of the encoder profile used for live capture */
+  encoderProfile: string;
+  filenameTemplate: string;
+  resolution: string;
+  frameRate: number;
+  /** Global hotkey starting a recording with this profile, empty for none */
+  hotkey: string;
+}
@@ -1,9 +1,16 @@
+import type { RecordingProfile } from "./recording";
+
 export interface Settings {
+  /** Schema version, migrated by the backend */
+  version: number;
   output: OutputSettings;
   hotkeys: HotkeySettings;
   recording: RecordingSettings;
   camera: CameraSettings;
   general: GeneralSettings;
+  audio: AudioSettings;
+  /** Named presets of mode, sources and output options */
+  profiles: RecordingProfile[];
   isFirstLaunch: boolean;
 }
 
@@ -12,6 +19,20 @@ export interface OutputSettings {
   resolution: string;
   frameRate: number;
   format: string;
+  /** Split into numbered parts at this size, 0 disables splitting */
+  maxFileSizeMb: number;
+  /** Split into numbered parts every N minutes, 0 disables splitting */
+  splitDurationMin: number;
+  lowDiskWarningMb: number;
+  minFreeSpaceMb: number;
+  /** e.g. "{yyyy}/{mm}/FlashScreen_{date}_{time}" */
+  filenameTemplate: string;
+  /** Encoder profile id used for live capture */
+  encoderProfile: string;
+  /** Export jobs that may run at the same time */
+  exportConcurrency: number;
+  /** Earlier output directories whose recordings stay in the library */
+  libraryDirectories: string[];
 }
 
 export interface HotkeySettings {
@@ -28,6 +49,90 @@ export interface RecordingSettings {
   highlightClicks: boolean;
   playStartSound: boolean;
   playEndSound: boolean;
+  /** Pause and error cues, volume and custom sound files */
+  sounds: SoundCues;
+  /** Style of the cursor effects; smoothing and ripples follow the toggles above */
+  cursorEffects: CursorEffects;
+  /** Keystroke capture and its caption, burned in at export */
+  keystrokes: KeystrokeOverlay;
+  /** Zoom-and-pan toward clicks, rendered as a copy after recording */
+  autoZoom: AutoZoom;
+}
+
+export interface AutoZoom {
+  enabled: boolean;
+  /** Zoom factor around clicks, 2 shows half the width */
+  scale: number;
+  /** How long the view stays zoomed after the last click, in milliseconds */
+  holdMs: number;
+  /** Duration of zooming in, out and panning, in milliseconds */
+  transitionMs: number;
+}
+
+/** Point of the zoom timeline; the view eases between consecutive points */
+export interface ZoomKeyframe {
+  /** Milliseconds since the recording started */
+  t: number;
+  /** 1 shows the whole frame */
+  zoom: number;
+  /** Center of the view relative to the frame, 0 to 1 */
+  x: number;
+  y: number;
+  /** Placed by hand; kept when the timeline is regenerated */
+  manual: boolean;
+}
+
+/** Moment of a recording a sound cue is played for */
+export type SoundCue = "start" | "stop" | "pause" | "error";
+
+export interface SoundCues {
+  playPauseSound: boolean;
+  playErrorSound: boolean;
+  /** 0 to 1 */
+  volume: number;
+  /** Audio files replacing the bundled tones, WAV, FLAC, Ogg Vorbis or MP3 */
+  startFile: string | null;
+  stopFile: string | null;
+  pauseFile: string | null;
+  errorFile: string | null;
+}
+
+/** Cursor effects composited from the cursor telemetry */
+export interface CursorEffects {
+  /** How strongly movement is smoothed, 0 follows the pointer exactly */
+  smoothing: number;
+  clickRipples: boolean;
+  /** Dim everything but a circle around the cursor */
+  spotlight: boolean;
+  cursorScale: number;
+  /** "#RRGGBB" */
+  rippleColor: string;
+  /** Spotlight radius in video pixels */
+  spotlightRadius: number;
+}
+
+/** Which key presses are recorded; "shortcutsOnly" never stores typed text */
+export type KeystrokeFilter = "shortcutsOnly" | "allKeys";
+
+export type OverlayPosition =
+  | "top-left"
+  | "top-center"
+  | "top-right"
+  | "bottom-left"
+  | "bottom-center"
+  | "bottom-right";
+
+export interface KeystrokeOverlay {
+  enabled: boolean;
+  filter: KeystrokeFilter;
+  position: OverlayPosition;
+  /** Font size in pixels of a 1080p video */
+  fontSize: number;
+  /** "#RRGGBB" */
+  textColor: string;
+  backgroundColor: string;
+  /** How long each caption stays on screen, in milliseconds */
+  displayMs: number;
 }
 
 export interface CameraSettings {
@@ -42,4 +147,86 @@ export interface GeneralSettings {
   launchAtStartup: boolean;
   minimizeToTray: boolean;
   showPreviewAfterRecording: boolean;
+  /** Desktop notifications for finished recordings and failures */
+  notifications: NotificationPreference;
+  /** Detail written to the log files */
+  logLevel: LogLevel;
+}
+
+export type NotificationPreference = "all" | "errorsOnly" | "off";
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+export type ProcessingMode = "live" | "post";
+
+export type NoiseSuppression = "off" | "fft" | "rnn";
+
+/** Processing chain for one audio source */
+export interface AudioProcessing {
+  enabled: boolean;
+  /** Apply while capturing or as a pass after the recording stops */
+  mode: ProcessingMode;
+  /** High-pass cutoff in Hz, 0 disables it */
+  highpassHz: number;
+  noiseSuppression: NoiseSuppression;
+  noiseReductionDb: number;
+  /** .rnnn model file, required for "rnn" */
+  rnnModel: string | null;
+  compressor: boolean;
+  loudnorm: boolean;
+  targetLufs: number;
+}
+
+export interface AudioSettings {
+  microphone: AudioProcessing;
+  systemAudio: AudioProcessing;
+}
+
+/** Something wrong with one setting */
+export interface SettingsProblem {
+  /** Path of the setting, e.g. "output.frameRate"; empty for the whole file */
+  key: string;
+  message: string;
+  /** The value was replaced by its default */
+  reset: boolean;
+}
+
+/** What went wrong loading the settings file at startup */
+export interface SettingsReport {
+  problems: SettingsProblem[];
+  /** Copy of the file as it was before it got repaired */
+  backup: string | null;
+}
+
+/** Sections of the settings that can be reset on their own */
+export type SettingsSection = Exclude<keyof Settings, "version" | "isFirstLaunch">;
+
+/** Payload of the `settings-changed` event */
+export interface SettingsChanged {
+  settings: Settings;
+  /** Dotted paths of the changed values, e.g. "output.frameRate" */
+  keys: string[];
+}
+
+/** Where a setting's value comes from, from lowest to highest precedence */
+export type SettingsLayer = "default" | "system" | "user" | "env" | "cli";
+
+/** Settings with the layer each value comes from */
+export interface SettingsView extends Settings {
+  /** Layer of each value, keyed like "output.frameRate" */
+  sources: Record<string, SettingsLayer>;
+  /** Keys set by the system policy, the environment or the command line */
+  locked: string[];
+}
+
+/** What happens to the recordings in the previous output directory */
+export type PreviousRecordings = "keep" | "move" | "index";
+
+/** Result of choosing a new output directory */
+export interface OutputDirectoryChanged {
+  directory: string;
+  /** Files moved from the previous directory */
+  moved: number;
+  /** Files that couldn't be moved, and why */
+  errors: string[];
 }